@@ -3,42 +3,83 @@
 in vec3 v_normal;
 in vec3 v_position;
 in vec2 v_tex_coords;
+in vec3 v_camera_offset;
 flat in uint v_texture_id;
+// Sunlight and block light reaching the face, from 0 to 1
+in vec2 v_light;
 
 out vec4 color;
 
+// Direction towards the sun or moon in world space, and the colour and strength of its light
 uniform vec3 u_light;
-uniform sampler2D tex0;
-uniform sampler2D tex1;
-uniform sampler2D tex2;
-uniform sampler2D tex3;
-uniform sampler2D tex4;
+uniform vec3 u_light_colour;
+uniform float u_light_strength;
+uniform float u_ambient_strength;
+// Depth from the light of every shadow cascade, and the view depths where each of them ends
+uniform bool u_shadows;
+uniform sampler2DArrayShadow u_shadow_map;
+uniform mat4 u_light_spaces[3];
+uniform vec3 u_cascade_ends;
+// One layer per block texture, picked by the vertex's texture id
+uniform sampler2DArray textures;
+uniform vec3 u_sky_zenith;
+uniform vec3 u_sky_horizon;
+// Horizontal distances where fog starts and where it hides the terrain completely
+uniform float u_fog_start;
+uniform float u_fog_end;
 
 const vec3 ambient_color = vec3(0.7, 0.7, 0.7);
-const float diffuse_strength = 0.3;
-const float ambient_strength = 0.7;
+const vec3 block_light_color = vec3(1.0, 0.8, 0.55);
+// Every light level is this much dimmer than the one above it
+const float light_falloff = 0.8;
 
-void main() {
-    vec3 diffuse_color;
-    if (v_texture_id == 0u) {
-        diffuse_color = texture(tex0, v_tex_coords).rgb;
-    } else if (v_texture_id == 1u) {
-        diffuse_color = texture(tex1, v_tex_coords).rgb;
-    } else if (v_texture_id == 2u) {
-        diffuse_color = texture(tex2, v_tex_coords).rgb;
-    } else if (v_texture_id == 3u) {
-        diffuse_color = texture(tex3, v_tex_coords).rgb;
-    } else if (v_texture_id == 4u) {
-        diffuse_color = texture(tex4, v_tex_coords).rgb;
+// Brightness of a light level given from 0 to 1, even level 0 isn't completely black
+float brightness(float level) {
+    return pow(light_falloff, (1.0 - level) * 15.0);
+}
+
+// How much of the light reaches this fragment, averaged over the 3x3 shadow map texels around it
+float shadow(vec3 normal) {
+    float depth = -v_position.z;
+    if (!u_shadows || depth >= u_cascade_ends.z) {
+        return 1.0;
+    }
+    int cascade = depth < u_cascade_ends.x ? 0 : (depth < u_cascade_ends.y ? 1 : 2);
+
+    // Pushed out along the normal, further in the bigger cascades, so faces don't shadow themselves
+    vec3 offset = normal * 0.04 * float(cascade + 1);
+    vec4 light_position = u_light_spaces[cascade] * vec4(v_camera_offset + offset, 1.0);
+    vec3 coords = light_position.xyz / light_position.w * 0.5 + 0.5;
+
+    vec2 texel = 1.0 / vec2(textureSize(u_shadow_map, 0).xy);
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            lit += texture(u_shadow_map, vec4(coords.xy + vec2(x, y) * texel, float(cascade), coords.z - 0.0005));
+        }
     }
+    return lit / 9.0;
+}
+
+void main() {
+    vec3 diffuse_color = texture(textures, vec3(v_tex_coords, float(v_texture_id))).rgb;
 
     vec3 normalized_normal = normalize(v_normal);
     float diffuse = max(dot(normalized_normal, normalize(u_light)), 0.0);
 
+    diffuse *= shadow(normalized_normal);
+
     // Combine ambient and diffuse lighting
-    vec3 lighting = ambient_strength * ambient_color + diffuse_strength * diffuse * vec3(1.0);
+    // The sun and moon only light what the sky can reach, torches and lava light up the rest
+    vec3 sky_lighting = u_ambient_strength * ambient_color + u_light_strength * diffuse * u_light_colour;
+    vec3 block_lighting = brightness(v_light.y) * block_light_color;
+    vec3 lighting = max(sky_lighting * brightness(v_light.x), block_lighting);
     vec3 final_color = diffuse_color * lighting;
 
-    // Set final color directly without fog
-    color = vec4(final_color, 1.0);
+    // Fade into the sky behind the terrain, the same gradient `sky_fragment.glsl` draws
+    vec3 direction = normalize(v_camera_offset);
+    vec3 sky_color = mix(u_sky_horizon, u_sky_zenith, sqrt(max(direction.y, 0.0)));
+    float fog = smoothstep(u_fog_start, u_fog_end, length(v_camera_offset.xz));
+
+    color = vec4(mix(final_color, sky_color, fog), 1.0);
 }
\ No newline at end of file
@@ -4,11 +4,15 @@ in vec3 position;
 in vec3 normal;
 in vec2 tex_coords;
 in uint texture_id;
+in vec2 light;
 
 out vec3 v_normal;
 out vec3 v_position;
 out vec2 v_tex_coords;
+// Position relative to the camera, world space is centred on it
+out vec3 v_camera_offset;
 flat out uint v_texture_id;
+out vec2 v_light;
 
 uniform mat4 perspective;
 uniform mat4 view;
@@ -17,12 +21,14 @@ uniform mat4 model;
 void main() {
     v_tex_coords = tex_coords;
     v_texture_id = texture_id;
-    mat4 modelview = view * model;
-    v_normal = transpose(inverse(mat3(modelview))) * normal;
+    v_light = light;
+    // Lit in world space, where the sun is
+    v_normal = transpose(inverse(mat3(model))) * normal;
 
     // Snap vertices to grid
     vec3 snapped_position = round(position);
     vec4 world_pos = model * vec4(snapped_position, 1.0);
     gl_Position = perspective * view * world_pos;
     v_position = (view * world_pos).xyz;
+    v_camera_offset = world_pos.xyz;
 }
\ No newline at end of file
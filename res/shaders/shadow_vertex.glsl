@@ -0,0 +1,11 @@
+#version 140
+
+in vec3 position;
+
+uniform mat4 model;
+// Camera relative world space to the light's clip space, for the cascade being drawn
+uniform mat4 light_space;
+
+void main() {
+    gl_Position = light_space * model * vec4(round(position), 1.0);
+}
\ No newline at end of file
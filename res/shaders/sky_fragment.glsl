@@ -0,0 +1,19 @@
+#version 140
+
+in vec2 v_screen_position;
+
+out vec4 color;
+
+// Takes a point on the screen back to a direction from the camera
+uniform mat4 inverse_view_projection;
+uniform vec3 u_sky_zenith;
+uniform vec3 u_sky_horizon;
+
+void main() {
+    vec4 far_point = inverse_view_projection * vec4(v_screen_position, 1.0, 1.0);
+    vec3 direction = normalize(far_point.xyz / far_point.w);
+
+    // Same gradient as `SkyColours::colour`
+    float height = sqrt(max(direction.y, 0.0));
+    color = vec4(mix(u_sky_horizon, u_sky_zenith, height), 1.0);
+}
@@ -0,0 +1,10 @@
+#version 140
+
+out vec2 v_screen_position;
+
+void main() {
+    // One triangle covering the whole screen, made from the vertex index alone
+    vec2 position = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2)) * 2.0 - 1.0;
+    v_screen_position = position;
+    gl_Position = vec4(position, 1.0, 1.0);
+}
@@ -1,7 +1,12 @@
 use cgmath::{InnerSpace, Matrix4, Point3, Vector3};
 
+use super::frustum::Frustum;
+
+// The camera sits at the origin of render space and everything is drawn relative to it.
+// Positions are kept in f64 and only the offset from the camera is turned into f32,
+// so the world stays as precise thousands of chunks out as it is at spawn.
 pub struct Camera {
-   pub position: Point3<f32>,
+   pub position: Point3<f64>,
    pub front: Vector3<f32>,
    pub up: Vector3<f32>,
    pub right: Vector3<f32>,
@@ -12,7 +17,7 @@ pub struct Camera {
 }
 
 impl Camera {
-   pub fn new(position: Point3<f32>, up: Vector3<f32>, yaw: f32, pitch: f32) -> Self {
+   pub fn new(position: Point3<f64>, up: Vector3<f32>, yaw: f32, pitch: f32) -> Self {
         let mut camera = Camera {
             position,
             front: Vector3::new(0.0, 0.0, -1.0),
@@ -27,8 +32,28 @@ impl Camera {
         camera
     }
 
+   // Only rotates, the camera's position is taken out of the model matrices instead
    pub fn get_view_matrix(&self) -> Matrix4<f32> {
-        Matrix4::look_at_rh(self.position, self.position + self.front, self.up)
+        Matrix4::look_to_rh(Point3::new(0.0, 0.0, 0.0), self.front, self.up)
+    }
+
+   // What the camera can see, in render space like everything else relative to the camera
+   pub fn frustum(&self, perspective: Matrix4<f32>) -> Frustum {
+        Frustum::from_matrix(perspective * self.get_view_matrix())
+    }
+
+   // Where a point in the world is in render space
+   pub fn relative_position(&self, world_position: Point3<f64>) -> Vector3<f32> {
+        camera_relative(world_position, self.position)
+    }
+
+   // Model matrix placing something with its origin at a point in the world
+   pub fn model_matrix(&self, world_position: Point3<f64>) -> Matrix4<f32> {
+        Matrix4::from_translation(self.relative_position(world_position))
+    }
+
+   pub fn translate(&mut self, offset: Vector3<f32>) {
+        self.position += Vector3::new(offset.x as f64, offset.y as f64, offset.z as f64);
     }
 
    pub fn update_camera_vectors(&mut self) {
@@ -54,4 +79,10 @@ impl Camera {
         self.update_camera_vectors();
     }
     
-}
\ No newline at end of file
+}
+
+// Offset from the camera to a point, subtracted in f64 before the precision is dropped
+pub fn camera_relative(world_position: Point3<f64>, camera_position: Point3<f64>) -> Vector3<f32> {
+    let offset = world_position - camera_position;
+    Vector3::new(offset.x as f32, offset.y as f32, offset.z as f32)
+}
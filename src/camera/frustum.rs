@@ -0,0 +1,66 @@
+use cgmath::{InnerSpace, Matrix, Matrix4, Point3, Vector3, Vector4};
+
+// A plane with everything on the side its normal points to counted as inside
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    // Plane from the (a, b, c, d) of ax + by + cz + d = 0, normalized so `distance_to` is in world units
+    fn from_coefficients(coefficients: Vector4<f32>) -> Self {
+        let normal = coefficients.truncate();
+        let length = normal.magnitude();
+        Plane {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        }
+    }
+
+    // Signed distance, negative behind the plane
+    pub fn distance_to(&self, point: Point3<f32>) -> f32 {
+        self.normal.dot(Vector3::new(point.x, point.y, point.z)) + self.distance
+    }
+}
+
+// The six planes bounding what a camera can see: left, right, bottom, top, near and far
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    // Planes of an OpenGL style view-projection matrix, clip space running from -w to w on every axis
+    pub fn from_matrix(view_projection: Matrix4<f32>) -> Self {
+        let row = |index| view_projection.row(index);
+        Frustum {
+            planes: [
+                Plane::from_coefficients(row(3) + row(0)),
+                Plane::from_coefficients(row(3) - row(0)),
+                Plane::from_coefficients(row(3) + row(1)),
+                Plane::from_coefficients(row(3) - row(1)),
+                Plane::from_coefficients(row(3) + row(2)),
+                Plane::from_coefficients(row(3) - row(2)),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.planes.iter().all(|plane| plane.distance_to(point) >= 0.0)
+    }
+
+    // False only if the box is completely behind one of the planes. Boxes near a corner of the
+    // frustum can pass without being visible, which only costs a wasted draw call.
+    pub fn intersects_aabb(&self, min: Point3<f32>, max: Point3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            // The box's corner furthest along the plane's normal
+            let corner = Point3::new(
+                if plane.normal.x >= 0.0 { max.x } else { min.x },
+                if plane.normal.y >= 0.0 { max.y } else { min.y },
+                if plane.normal.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.distance_to(corner) >= 0.0
+        })
+    }
+}
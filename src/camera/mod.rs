@@ -1 +1,3 @@
-pub mod camera;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod camera;
+pub mod frustum;
\ No newline at end of file
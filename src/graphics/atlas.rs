@@ -0,0 +1,122 @@
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+
+// Where a tile is in the atlas: the texture array layer of its page and the texture coordinates
+// of its corners, bottom left, bottom right, top right and top left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileUVs {
+    pub layer: u32,
+    pub corners: [(f32, f32); 4],
+}
+
+// How block textures are laid out on the pages of the atlas. Every tile sits in a cell with its
+// edge pixels repeated around it, so sampling right at a tile's edge never picks up its neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasLayout {
+    // Width and height of a tile, tiles of any other size are scaled to it
+    pub tile_size: u32,
+    // Repeated edge pixels on each side of a tile
+    pub padding: u32,
+    // Width and height of a page
+    pub page_size: u32,
+}
+
+impl Default for AtlasLayout {
+    fn default() -> Self {
+        AtlasLayout::for_tile_size(32)
+    }
+}
+
+impl AtlasLayout {
+    // Tiles padded by half their size on every side, 16 cells to a row
+    pub fn for_tile_size(tile_size: u32) -> Self {
+        AtlasLayout {
+            tile_size,
+            padding: tile_size / 2,
+            page_size: tile_size * 2 * 16,
+        }
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.tile_size + 2 * self.padding
+    }
+
+    pub fn tiles_per_row(&self) -> u32 {
+        self.page_size / self.cell_size()
+    }
+
+    pub fn tiles_per_page(&self) -> u32 {
+        self.tiles_per_row() * self.tiles_per_row()
+    }
+
+    // Mipmap levels below the full size that don't bleed between tiles. Halving a page averages 2x2
+    // blocks of pixels, which stays inside a cell as long as its tile and padding still start on a
+    // whole pixel, and at least a pixel of padding is left for sampling right at a tile's edge.
+    pub fn mip_levels(&self) -> u32 {
+        let mut levels = 0;
+        loop {
+            // How much smaller the next level is
+            let scale = 2 << levels;
+            if self.padding < scale || !self.padding.is_multiple_of(scale) || !self.tile_size.is_multiple_of(scale) {
+                return levels;
+            }
+            levels += 1;
+        }
+    }
+
+    pub fn page_count(&self, tile_count: u32) -> u32 {
+        tile_count.div_ceil(self.tiles_per_page()).max(1)
+    }
+
+    // Page of a tile and the pixel position of its top left corner on it, padding excluded
+    pub fn tile_position(&self, tile: u32) -> (u32, u32, u32) {
+        let page = tile / self.tiles_per_page();
+        let cell = tile % self.tiles_per_page();
+        let x = (cell % self.tiles_per_row()) * self.cell_size() + self.padding;
+        let y = (cell / self.tiles_per_row()) * self.cell_size() + self.padding;
+        (page, x, y)
+    }
+
+    // Pages are uploaded bottom row first, so v runs up from the bottom of the image
+    pub fn tile_uvs(&self, tile: u32) -> TileUVs {
+        let (page, x, y) = self.tile_position(tile);
+        let size = self.page_size as f32;
+        let (u1, u2) = (x as f32 / size, (x + self.tile_size) as f32 / size);
+        let (v1, v2) = (1.0 - (y + self.tile_size) as f32 / size, 1.0 - y as f32 / size);
+
+        TileUVs {
+            layer: page,
+            corners: [(u1, v1), (u2, v1), (u2, v2), (u1, v2)],
+        }
+    }
+
+    // Lay tiles out on as many pages as they need, in order, scaled to the tile size
+    pub fn pack(&self, tiles: &[RgbaImage]) -> Vec<RgbaImage> {
+        let mut pages: Vec<RgbaImage> = (0..self.page_count(tiles.len() as u32))
+            .map(|_| RgbaImage::new(self.page_size, self.page_size))
+            .collect();
+
+        for (index, tile) in tiles.iter().enumerate() {
+            let scaled;
+            let tile = if tile.dimensions() == (self.tile_size, self.tile_size) {
+                tile
+            } else {
+                scaled = imageops::resize(tile, self.tile_size, self.tile_size, FilterType::Triangle);
+                &scaled
+            };
+
+            let (page, x, y) = self.tile_position(index as u32);
+            let (cell_x, cell_y) = (x - self.padding, y - self.padding);
+            // Every pixel of the cell takes the colour of the closest pixel of the tile
+            for dy in 0..self.cell_size() {
+                for dx in 0..self.cell_size() {
+                    let source_x = dx.saturating_sub(self.padding).min(self.tile_size - 1);
+                    let source_y = dy.saturating_sub(self.padding).min(self.tile_size - 1);
+                    pages[page as usize].put_pixel(cell_x + dx, cell_y + dy, *tile.get_pixel(source_x, source_y));
+                }
+            }
+        }
+
+        pages
+    }
+}
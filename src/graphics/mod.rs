@@ -1,2 +1,4 @@
+pub mod atlas;
 pub mod texture;
-pub mod cube;
\ No newline at end of file
+pub mod cube;
+pub mod texture_pack;
\ No newline at end of file
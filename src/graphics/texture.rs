@@ -1,70 +1,58 @@
-use std::{cell::OnceCell, collections::HashMap, sync::OnceLock};
+use std::fmt;
+use std::sync::OnceLock;
 
-use glium::{glutin::surface::WindowSurface, Display};
-use glium::texture::{RawImage2d, Texture2dArray};
+use glium::glutin::surface::WindowSurface;
+use image::RgbaImage;
 
-use super::cube::FaceUVs;
+use crate::world::block::Block;
 
-pub fn calculate_tile_uvs(tile_x: u32, tile_y: u32) -> [(f32, f32); 4] {
-    let tile_size: f32 = 64.0;
-    let atlas_size: f32 = 1024.0;
-    
-    // Calculate the UV coordinates for the tile
-    let u1: f32 = (tile_x as f32 * tile_size) / atlas_size;
-    let v1: f32 = (tile_y as f32 * tile_size) / atlas_size;
-    let u2: f32 = u1 + (tile_size / atlas_size);
-    let v2: f32 = v1 + (tile_size / atlas_size);
+use super::cube::FaceUVs;
+use super::texture_pack::TextureManifest;
 
-    [
-        (u1, v1), // Bottom-left
-        (u2, v1), // Bottom-right
-        (u2, v2), // Top-right
-        (u1, v2), // Top-left
-    ]
-}
+// The default pack's manifest, built in so meshing never depends on the working directory
+const DEFAULT_MANIFEST: &str = include_str!("../../res/blocks/pack.txt");
 
+// Atlas UVs of every block's faces, indexed by block
+static UVS: OnceLock<Vec<FaceUVs>> = OnceLock::new();
 
-pub static UVS: OnceLock<HashMap<String, FaceUVs>> = OnceLock::new();
+// The UVs were set already, by an earlier call or by meshing a chunk before any pack was picked
+#[derive(Debug)]
+pub struct UvsAlreadySet;
 
-pub fn init_uvs() {
-    UVS.get_or_init(|| {
-        let mut map = HashMap::new();
-        map.insert("grass".to_string(), FaceUVs {
-            front: calculate_tile_uvs(3, 15),
-            back: calculate_tile_uvs(3, 15),
-            top: calculate_tile_uvs(0, 16),
-            bottom: calculate_tile_uvs(2, 16),
-            right: calculate_tile_uvs(3, 15),
-            left: calculate_tile_uvs(3, 15),
-        });
-        map.insert("light_grass".to_string(), FaceUVs {
-            front: calculate_tile_uvs(13, 2),
-            back: calculate_tile_uvs(13, 2),
-            top: calculate_tile_uvs(13, 2),
-            bottom: calculate_tile_uvs(13, 2),
-            right: calculate_tile_uvs(13, 2),
-            left: calculate_tile_uvs(13, 2),
-        });
-        map.insert("dark_grass".to_string(), FaceUVs {
-            front: calculate_tile_uvs(13, 0),
-            back: calculate_tile_uvs(13, 0),
-            top: calculate_tile_uvs(13, 0),
-            bottom: calculate_tile_uvs(13, 0),
-            right: calculate_tile_uvs(13, 0),
-            left: calculate_tile_uvs(13, 0),
-        });
-        map
-    });
+impl fmt::Display for UvsAlreadySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block UVs were already set, the texture pack has to be picked once before any chunk is meshed")
+    }
 }
 
+impl std::error::Error for UvsAlreadySet {}
 
-pub fn get_uvs(name: &str) -> Option<FaceUVs> {
-    UVS.get().and_then(|map| map.get(name)).cloned()
+// Mesh blocks with the UVs of a pack's atlas, has to happen before the first chunk is meshed
+pub fn init_uvs(manifest: &TextureManifest) -> Result<(), UvsAlreadySet> {
+    UVS.set(manifest.block_uvs()).map_err(|_| UvsAlreadySet)
+}
+
+// Atlas UVs of a block's faces, from the default texture pack if no other one was picked
+pub fn get_uvs(block: Block) -> &'static FaceUVs {
+    let uvs = UVS.get_or_init(|| {
+        TextureManifest::parse(DEFAULT_MANIFEST)
+            .unwrap_or_else(|error| panic!("the built-in default texture manifest is broken: {}", error))
+            .block_uvs()
+    });
+    &uvs[block as usize]
 }
 
-pub fn create_texture(display: &glium::Display<WindowSurface>, data: &[u8]) -> glium::Texture2d {
-    let image = image::load_from_memory(data).unwrap().to_rgba8();
-    let dimensions = image.dimensions();
-    let image = glium::texture::RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
-    glium::Texture2d::new(display, image).unwrap()
+// Stack same sized images into the layers of one texture array, in order, so a layer index picks the image.
+// The GPU averages each mipmap level from the one above it, only `mip_levels` of them are made.
+pub fn create_texture_array(display: &glium::Display<WindowSurface>, layers: Vec<RgbaImage>, mip_levels: u32) -> glium::texture::Texture2dArray {
+    let dimensions = layers[0].dimensions();
+    for (layer, image) in layers.iter().enumerate() {
+        assert_eq!(image.dimensions(), dimensions, "texture layer {} doesn't match the size of the first layer", layer);
+    }
+
+    let layers = layers
+        .into_iter()
+        .map(|image| glium::texture::RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions))
+        .collect();
+    glium::texture::Texture2dArray::with_mipmaps(display, layers, glium::texture::MipmapsOption::AutoGeneratedMipmapsMax(mip_levels)).unwrap()
 }
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use image::{ColorType, ImageFormat, Rgba, RgbaImage};
+
+use crate::world::block::Block;
+
+use super::atlas::AtlasLayout;
+use super::cube::FaceUVs;
+
+// Pack used when no other one is picked
+pub const DEFAULT_TEXTURE_PACK: &str = "res/blocks";
+// File in a pack's directory listing its images and the blocks they go on
+pub const MANIFEST_FILE: &str = "pack.txt";
+// Image name of the built-in tile put on blocks a pack has no texture for
+pub const MISSING_TEXTURE: &str = "<missing>";
+
+#[derive(Debug)]
+pub enum TexturePackError {
+    Io(PathBuf, io::Error),
+    // A manifest line that couldn't be understood, lines count from 1
+    Manifest { line: usize, message: String },
+    Image(PathBuf, image::ImageError),
+    // Only 8 bit PNGs are supported
+    UnsupportedFormat(PathBuf, String),
+    NotSquare { path: PathBuf, width: u32, height: u32 },
+    // Every image of a pack has to be the same size as the first one
+    SizeMismatch { path: PathBuf, expected: u32, found: (u32, u32) },
+}
+
+impl fmt::Display for TexturePackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TexturePackError::Io(path, error) => write!(f, "couldn't read {}: {}", path.display(), error),
+            TexturePackError::Manifest { line, message } => write!(f, "{} line {}: {}", MANIFEST_FILE, line, message),
+            TexturePackError::Image(path, error) => write!(f, "couldn't load {}: {}", path.display(), error),
+            TexturePackError::UnsupportedFormat(path, format) => {
+                write!(f, "{} is {}, only 8 bit PNGs are supported", path.display(), format)
+            }
+            TexturePackError::NotSquare { path, width, height } => {
+                write!(f, "{} is {}x{}, textures have to be square", path.display(), width, height)
+            }
+            TexturePackError::SizeMismatch { path, expected, found: (width, height) } => write!(
+                f,
+                "{} is {}x{}, the other textures of the pack are {}x{}",
+                path.display(),
+                width,
+                height,
+                expected,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TexturePackError {}
+
+// What a pack's manifest says, which images it has and which of them go on each block's faces.
+//
+//     # Size tiles are scaled to in the atlas
+//     tile_size = 32
+//     # A block and its image, or its top, side and bottom images
+//     dark-grass = dark-grass.png grass-side.png brown.png
+//     rock = rock-1.png
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureManifest {
+    pub tile_size: u32,
+    // Image files in the order they are packed, each listed once
+    pub images: Vec<String>,
+    // Index in `images` of the top, side and bottom image of each block, indexed by block
+    faces: Vec<Option<[usize; 3]>>,
+    // Blocks the manifest has no texture for, they get the missing texture instead
+    pub missing_blocks: Vec<Block>,
+}
+
+impl TextureManifest {
+    pub fn parse(text: &str) -> Result<Self, TexturePackError> {
+        let mut manifest = TextureManifest {
+            tile_size: AtlasLayout::default().tile_size,
+            images: Vec::new(),
+            faces: vec![None; Block::ALL.len()],
+            missing_blocks: Vec::new(),
+        };
+
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| TexturePackError::Manifest { line: index + 1, message };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `name = value`, found {:?}", line)));
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "tile_size" {
+                manifest.tile_size = match value.parse() {
+                    Ok(size) if size > 0 => size,
+                    _ => return Err(error(format!("tile_size has to be a positive whole number, found {:?}", value))),
+                };
+                continue;
+            }
+
+            let block = Block::from_name(key).ok_or_else(|| error(format!("unknown block {:?}", key)))?;
+            let images: Vec<usize> = value.split_whitespace().map(|image| manifest.image_index(image)).collect();
+            let faces = match images[..] {
+                [image] => [image; 3],
+                [top, side, bottom] => [top, side, bottom],
+                _ => return Err(error(format!("{} needs one image or a top, side and bottom image", key))),
+            };
+            manifest.faces[block as usize] = Some(faces);
+        }
+
+        // Air is never drawn, every other block needs a texture and stands out if it doesn't have one
+        for block in Block::ALL {
+            if block.is_air() || manifest.faces[block as usize].is_some() {
+                continue;
+            }
+            let missing = manifest.image_index(MISSING_TEXTURE);
+            manifest.faces[block as usize] = Some([missing; 3]);
+            manifest.missing_blocks.push(block);
+        }
+        Ok(manifest)
+    }
+
+    pub fn load(directory: impl AsRef<Path>) -> Result<Self, TexturePackError> {
+        let path = directory.as_ref().join(MANIFEST_FILE);
+        let text = fs::read_to_string(&path).map_err(|error| TexturePackError::Io(path, error))?;
+        Self::parse(&text)
+    }
+
+    fn image_index(&mut self, image: &str) -> usize {
+        self.images.iter().position(|known| known == image).unwrap_or_else(|| {
+            self.images.push(image.to_string());
+            self.images.len() - 1
+        })
+    }
+
+    pub fn layout(&self) -> AtlasLayout {
+        AtlasLayout::for_tile_size(self.tile_size)
+    }
+
+    // Images on the top, sides and bottom of a block
+    pub fn block_images(&self, block: Block) -> Option<[&str; 3]> {
+        self.faces[block as usize].map(|faces| faces.map(|image| self.images[image].as_str()))
+    }
+
+    // Where each face of every block is in the pack's atlas, indexed by block
+    pub fn block_uvs(&self) -> Vec<FaceUVs> {
+        let layout = self.layout();
+        self.faces
+            .iter()
+            .map(|faces| {
+                // Air gets the first tile, it's never meshed anyway
+                let [top, side, bottom] = faces.unwrap_or_default().map(|image| layout.tile_uvs(image as u32));
+                FaceUVs { front: side, back: side, top, bottom, right: side, left: side }
+            })
+            .collect()
+    }
+}
+
+// A texture pack loaded from its directory, all of its images checked to be usable together
+pub struct TexturePack {
+    pub directory: PathBuf,
+    pub manifest: TextureManifest,
+    // The manifest's images, in the same order
+    pub tiles: Vec<RgbaImage>,
+}
+
+impl TexturePack {
+    pub fn load(directory: impl AsRef<Path>) -> Result<Self, TexturePackError> {
+        let directory = directory.as_ref().to_path_buf();
+        let manifest = TextureManifest::load(&directory)?;
+
+        let mut tiles: Vec<RgbaImage> = Vec::with_capacity(manifest.images.len());
+        for image in &manifest.images {
+            let path = directory.join(image);
+            let tile = if image == MISSING_TEXTURE {
+                missing_texture(tiles.first().map_or(manifest.tile_size, |first| first.width()))
+            } else {
+                load_tile(&path)?
+            };
+
+            let (width, height) = tile.dimensions();
+            if width != height {
+                return Err(TexturePackError::NotSquare { path, width, height });
+            }
+            if let Some(first) = tiles.first() {
+                if first.width() != width {
+                    return Err(TexturePackError::SizeMismatch { path, expected: first.width(), found: (width, height) });
+                }
+            }
+            tiles.push(tile);
+        }
+
+        Ok(TexturePack { directory, manifest, tiles })
+    }
+
+    // Every image of the pack laid out on atlas pages
+    pub fn atlas_pages(&self) -> Vec<RgbaImage> {
+        self.manifest.layout().pack(&self.tiles)
+    }
+}
+
+// Magenta and black checkers, hard to miss on a block
+pub fn missing_texture(size: u32) -> RgbaImage {
+    let half = (size / 2).max(1);
+    RgbaImage::from_fn(size, size, |x, y| {
+        if (x / half + y / half).is_multiple_of(2) {
+            Rgba([255, 0, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+// Checks the file really is a PNG, whatever it's called, with 8 bits per channel
+fn load_tile(path: &Path) -> Result<RgbaImage, TexturePackError> {
+    let reader = image::io::Reader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|error| TexturePackError::Io(path.to_path_buf(), error))?;
+    match reader.format() {
+        Some(ImageFormat::Png) => {}
+        format => {
+            let format = format.map_or("not an image".to_string(), |format| format!("{:?}", format));
+            return Err(TexturePackError::UnsupportedFormat(path.to_path_buf(), format));
+        }
+    }
+
+    let image = reader.decode().map_err(|error| TexturePackError::Image(path.to_path_buf(), error))?;
+    if !matches!(image.color(), ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8) {
+        return Err(TexturePackError::UnsupportedFormat(path.to_path_buf(), format!("{:?}", image.color())));
+    }
+    Ok(image.to_rgba8())
+}
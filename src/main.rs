@@ -1,25 +1,52 @@
 # ![windows_subsystem = "windows"]
 
-use std::fs::read_to_string;
-
 use glium::winit::event::{ElementState, MouseButton};
 use glium::Surface;
 use pixelquest::camera::camera::Camera;
-use pixelquest::constants::world::{CHUNK_SIZE, CUBE_INDICES};
-use pixelquest::graphics::cube::create_single_tx_cube_vertices;
-use pixelquest::graphics::texture::{calculate_tile_uvs, create_texture, init_uvs, UVS};
-use pixelquest::renderer::renderer::Renderer;
+use pixelquest::graphics::texture::init_uvs;
+use pixelquest::graphics::texture_pack::{TexturePack, DEFAULT_TEXTURE_PACK, MANIFEST_FILE};
+use pixelquest::renderer::daylight::daylight;
+use pixelquest::renderer::renderer::{RenderStats, Renderer};
+use pixelquest::renderer::settings::{RenderSettings, TextureFiltering};
 use device_query::{DeviceQuery, DeviceState, Keycode};
-use cgmath::{perspective, Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
-use pixelquest::world::chunk::{generate_chunk, Chunk, ChunkData};
+use cgmath::{perspective, Deg, Matrix4, Point3, Vector3};
+use pixelquest::world::block::Block;
+use pixelquest::world::clock::WorldClock;
+use pixelquest::world::coords::BlockPos;
+use pixelquest::world::preset::WorldPreset;
 
 use pixelquest::world::world::World;
 
+// TODO: move to the `ApplicationHandler` api, `EventLoop::run` is deprecated in this winit version
+#[allow(deprecated)]
 fn main() {
+    // Pick the world type from the first argument, e.g. `cargo run -- islands`
+    let preset = match std::env::args().nth(1) {
+        Some(name) => WorldPreset::from_name(&name).unwrap_or_else(|| {
+            let names: Vec<&str> = WorldPreset::ALL.iter().map(|preset| preset.name()).collect();
+            eprintln!("unknown world preset {:?}, expected one of {}", name, names.join(", "));
+            std::process::exit(2)
+        }),
+        None => WorldPreset::Default,
+    };
+
+    // Block textures come from the directory in the second argument, e.g. `cargo run -- default my-pack`
+    let texture_pack_directory = std::env::args().nth(2).unwrap_or_else(|| DEFAULT_TEXTURE_PACK.to_string());
+    let texture_pack = TexturePack::load(&texture_pack_directory).unwrap_or_else(|error| {
+        eprintln!("failed to load texture pack {:?}: {}", texture_pack_directory, error);
+        std::process::exit(2)
+    });
+    for block in &texture_pack.manifest.missing_blocks {
+        eprintln!("warning: {} has no texture for {}, using the missing texture", MANIFEST_FILE, block.name());
+    }
+    // Chunks start meshing as soon as the world exists, so their UVs have to be known before that
+    if let Err(error) = init_uvs(&texture_pack.manifest) {
+        eprintln!("failed to use texture pack {:?}: {}", texture_pack_directory, error);
+        std::process::exit(2)
+    }
+
     let event_loop = glium::winit::event_loop::EventLoopBuilder::new().build().unwrap();
     let (window, display) = glium::backend::glutin::SimpleWindowBuilder::new().with_title("Pixel Quest").build(&event_loop);
-    
-    let mut current_mouse_position = (0.0, 0.0, 0.0);
 
     // initialize camera
     let mut camera = Camera::new(
@@ -29,23 +56,19 @@ fn main() {
         0.0,
     );
 
-
-    let offset = Vector3::new(0.0, 0.0, 0.0);
-
     let device_state = DeviceState::new();
+    let mut last_keys: Vec<Keycode> = Vec::new();
     let mut last_frame = std::time::Instant::now();
-
-    // For mouse control
-    let mut first_mouse = true;
-    let mut last_x = 400.0;
-    let mut last_y = 300.0;
+    // `last_frame` moves on with every event, the clock only advances once per frame
+    let mut last_clock_update = std::time::Instant::now();
+    let mut last_render_stats = RenderStats::default();
 
     // Capture the cursor
     window.set_cursor_grab(glium::winit::window::CursorGrabMode::Confined).unwrap();
     window.set_cursor_visible(false);
 
-    let mut world = World::new(&display);
-    let mut renderer = Renderer::new(&display);
+    let mut world = World::new(preset);
+    let mut renderer = Renderer::new(&display, &texture_pack, RenderSettings::default());
 
     let _ = event_loop.run(move |event, window_target| {
         let current_frame = std::time::Instant::now();
@@ -61,7 +84,9 @@ fn main() {
                 glium::winit::event::WindowEvent::RedrawRequested => {
                     // Start drawing the frame
                     let mut target = display.draw();
-                    target.clear_color_and_depth((0.0941, 0.2706, 0.9608, 1.0), 1.0);
+                    // The sky is drawn over all of it, this is only seen if it fails to, in the sky's colour where the camera looks
+                    let [r, g, b] = daylight(world.clock.time_of_day()).sky.colour(camera.front);
+                    target.clear_color_and_depth((r, g, b, 1.0), 1.0);
 
                     // Get window dimensions and aspect ratio
                     let (width, height) = target.get_dimensions();
@@ -71,69 +96,45 @@ fn main() {
                     let perspective: Matrix4<f32> = perspective(Deg(45.0), aspect_ratio, 0.1, 900.0);
                 
                     // Update world based on the camera's current position (for infinite terrain generation)
-                    world.update(camera.position, &display); 
+                    world.update(camera.position, camera.front, &display); 
                 
                     // Render the world with the updated camera and perspective
-                    renderer.render(&mut target, &world, &camera, perspective);
+                    let stats = renderer.render(&display, &mut target, &world, &camera, perspective);
+                    if stats != last_render_stats {
+                        window.set_title(&format!(
+                            "Pixel Quest - {} chunks drawn, {} culled, {} occluded",
+                            stats.chunks_drawn, stats.chunks_culled, stats.chunks_occluded
+                        ));
+                        last_render_stats = stats;
+                    }
                 
                     // Finalize drawing and display the frame
                     target.finish().unwrap();
                 },
-                glium::winit::event::WindowEvent::MouseInput { state, button, .. } => {
-                    if button == MouseButton::Left  {
-                     // Use the camera's current position to spawn the cube
-                     let cube_position: Point3<f32> = camera.position;
-                     let cube_position_vec: Vector3<f32> = Vector3::new(camera.position.x, camera.position.y, 0.0);
-
-                    // Access the chunks from World struct
-                     for chunk in world.chunks.iter_mut() {
-                   
-                    let new_cube_vertices = create_single_tx_cube_vertices(cube_position, offset, 1);
-        
-                    // Add vertices to chunk's vertex list
-                   let base_index = chunk.chunk_data.vertices.len() as u32;
-                   chunk.chunk_data.vertices.extend_from_slice(&new_cube_vertices);
-        
-                    // Generate indices for the new cube and add them to the chunk
-                   let new_cube_indices: Vec<u32> = CUBE_INDICES.iter()
-                   .map(|&idx| idx as u32 + base_index)
-                   .collect();
-                   chunk.chunk_data.indices.extend_from_slice(&new_cube_indices);
-        
-                   // Recreate the vertex and index buffers for the chunk
-                  chunk.vertex_buffer = glium::VertexBuffer::new(&display, &chunk.chunk_data.vertices).unwrap();
-                  chunk.index_buffer = glium::IndexBuffer::new(
-                &display,
-                glium::index::PrimitiveType::TrianglesList,
-                &chunk.chunk_data.indices
-                ).unwrap();
-        
-                println!("Added a new cube at camera position {:?}", cube_position);
-                break;
-                 }}
-                    // }
+                glium::winit::event::WindowEvent::MouseInput { button, state: ElementState::Pressed, .. } => {
+                    // Left click places grass, right click a torch
+                    let block = match button {
+                        MouseButton::Left => Block::LightGrass,
+                        MouseButton::Right => Block::Torch,
+                        _ => return,
+                    };
+
+                    // Use the camera's current position to spawn the cube, blocks are centred on whole coordinates
+                    let cube_position = BlockPos::containing(camera.position);
+
+                    // Place it in the chunk the camera is in
+                    if world.set_block(cube_position, block, &display) {
+                        println!("Added a new cube at camera position {:?}", cube_position);
+                    }
                 },
                 _ => (),
             },
-            glium::winit::event::Event::DeviceEvent { event, .. } => match event {
-                glium::winit::event::DeviceEvent::MouseMotion { delta } => {
-                    let (x, y) = delta;
-                    if first_mouse {
-                        last_x = x as f32;
-                        last_y = y as f32;
-                        first_mouse = false;
-                    }
-
-                    current_mouse_position = (last_x + x as f32, last_y + y as f32, 0.0);
-
-                    last_x += x as f32;
-                    last_y += y as f32;
+            glium::winit::event::Event::DeviceEvent { event: glium::winit::event::DeviceEvent::MouseMotion { delta }, .. } => {
+                let (x, y) = delta;
 
-                    // println!("Mouse coords: ({}, {})", x, -y);
+                // println!("Mouse coords: ({}, {})", x, -y);
 
-                    camera.process_mouse_movement(x as f32, -y as f32);
-                },
-                _ => (),            
+                camera.process_mouse_movement(x as f32, -y as f32);
             },
             glium::winit::event::Event::AboutToWait => {
                 // Handle keyboard input
@@ -141,20 +142,54 @@ fn main() {
                 let camera_speed = 60.0 * delta_time;
 
                 if keys.contains(&Keycode::W) {
-                    camera.position += camera.front * camera_speed;
+                    camera.translate(camera.front * camera_speed);
                 }
                 if keys.contains(&Keycode::S) {
-                    camera.position -= camera.front * camera_speed;
+                    camera.translate(-camera.front * camera_speed);
                 }
                 if keys.contains(&Keycode::A) {
-                    camera.position -= camera.right * camera_speed;
+                    camera.translate(-camera.right * camera_speed);
                 }
                 if keys.contains(&Keycode::D) {
-                    camera.position += camera.right * camera_speed;
+                    camera.translate(camera.right * camera_speed);
                 }
                 if keys.contains(&Keycode::W) && keys.contains(&Keycode::LShift) {
-                    camera.position += camera.front * camera_speed * 2.0
+                    camera.translate(camera.front * camera_speed * 2.0);
+                }
+
+                // Change the render distance with - and =, once per key press
+                let pressed = |key: Keycode| keys.contains(&key) && !last_keys.contains(&key);
+                let radius_change = if pressed(Keycode::Equal) { 1 } else if pressed(Keycode::Minus) { -1 } else { 0 };
+                if radius_change != 0 {
+                    world.set_render_distance(world.render_distance.load_radius + radius_change);
+                    println!("Render distance set to {} chunks", world.render_distance.load_radius);
+                }
+
+                // Step through the texture filtering with F, from blocky to smooth
+                if pressed(Keycode::F) {
+                    let filtering = renderer.settings.texture_filtering().map_or(TextureFiltering::ALL[0], TextureFiltering::next);
+                    renderer.settings.set_texture_filtering(filtering);
+                    println!("Texture filtering set to {}", filtering.name());
                 }
+
+                // Freeze the time of day with T, and jump to midnight, sunrise, noon or sunset with 1 to 4
+                if pressed(Keycode::T) {
+                    world.clock.frozen = !world.clock.frozen;
+                    println!("Time {}", if world.clock.frozen { "frozen" } else { "running" });
+                }
+                let times = [
+                    (Keycode::Key1, WorldClock::MIDNIGHT),
+                    (Keycode::Key2, WorldClock::SUNRISE),
+                    (Keycode::Key3, WorldClock::NOON),
+                    (Keycode::Key4, WorldClock::SUNSET),
+                ];
+                if let Some(&(_, time_of_day)) = times.iter().find(|(key, _)| pressed(*key)) {
+                    world.clock.set_time_of_day(time_of_day);
+                    println!("Time set to {:.2} of day {}", time_of_day, world.clock.day());
+                }
+                world.clock.advance((current_frame - last_clock_update).as_secs_f32());
+                last_clock_update = current_frame;
+                last_keys = keys;
                 
 
                 // println!("Camera position: {:?}", camera.position);
@@ -166,12 +201,3 @@ fn main() {
     });
 }
 
-fn is_position_in_chunk(position: Vector3<f32>, chunk: &Chunk) -> bool {
-    let chunk_x = chunk.position.x as f32 * 16 as f32;
-    let chunk_y = chunk.position.y as f32 * 16 as f32;
-    let chunk_z = chunk.position.z as f32 * 16 as f32;
-
-    position.x >= chunk_x && position.x < chunk_x + 16 as f32 &&
-    position.y >= chunk_y && position.y < chunk_y + 16 as f32 &&
-    position.z >= chunk_z && position.z < chunk_z + 16 as f32
-}
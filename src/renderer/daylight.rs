@@ -0,0 +1,93 @@
+use std::f32::consts::TAU;
+
+use cgmath::{InnerSpace, Vector3};
+
+use super::sky::SkyColours;
+
+// How far the sun's path leans away from passing straight overhead
+const SUN_PATH_TILT: f32 = 0.4;
+
+const SUN_COLOUR: [f32; 3] = [1.0, 0.97, 0.9];
+// Light and sky near the horizon while the sun rises or sets
+const SUNSET_COLOUR: [f32; 3] = [1.0, 0.55, 0.3];
+const MOON_COLOUR: [f32; 3] = [0.45, 0.55, 0.8];
+
+const NIGHT_SKY: SkyColours = SkyColours {
+    zenith: [0.005, 0.01, 0.04],
+    horizon: [0.03, 0.05, 0.12],
+};
+
+// Strength of the direct light with the sun and the moon high in the sky
+const SUN_STRENGTH: f32 = 0.3;
+const MOON_STRENGTH: f32 = 0.08;
+const DAY_AMBIENT: f32 = 0.7;
+const NIGHT_AMBIENT: f32 = 0.15;
+
+// Everything the time of day changes about how the world is lit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Daylight {
+    // Direction towards the sun, or the moon at night, in world space
+    pub light_direction: Vector3<f32>,
+    pub light_colour: [f32; 3],
+    pub light_strength: f32,
+    pub ambient_strength: f32,
+    pub sky: SkyColours,
+}
+
+// Direction towards the sun, it rises along +x, passes overhead at noon and is straight down at midnight
+pub fn sun_direction(time_of_day: f32) -> Vector3<f32> {
+    let angle = (time_of_day - 0.25) * TAU;
+    Vector3::new(angle.cos(), angle.sin() * SUN_PATH_TILT.cos(), angle.sin() * SUN_PATH_TILT.sin())
+}
+
+// The moon is always opposite the sun
+pub fn moon_direction(time_of_day: f32) -> Vector3<f32> {
+    -sun_direction(time_of_day)
+}
+
+// How the world is lit at a time of day, from 0 at midnight up to 1 the next midnight
+pub fn daylight(time_of_day: f32) -> Daylight {
+    let sun = sun_direction(time_of_day);
+    let moon = -sun;
+
+    // 0 at night, 1 during the day, with dawn and dusk in between while the sun is close to the horizon
+    let day = smoothstep(-0.15, 0.15, sun.y);
+    // Strongest with the sun right on the horizon
+    let twilight = 1.0 - (sun.y.abs() / 0.25).min(1.0);
+
+    // Whichever of the sun and moon is higher lights the world. Both are weakest on the horizon,
+    // so the light fades out before it switches between them.
+    let sun_strength = SUN_STRENGTH * smoothstep(-0.05, 0.2, sun.y);
+    let moon_strength = MOON_STRENGTH * smoothstep(-0.05, 0.2, moon.y);
+    let (light_direction, light_colour, light_strength) = if sun_strength >= moon_strength {
+        let colour = mix(SUNSET_COLOUR, SUN_COLOUR, smoothstep(0.0, 0.4, sun.y));
+        (sun, colour, sun_strength)
+    } else {
+        (moon, MOON_COLOUR, moon_strength)
+    };
+
+    let day_sky = SkyColours::default();
+    let horizon = mix(NIGHT_SKY.horizon, day_sky.horizon, day);
+    let sky = SkyColours {
+        zenith: mix(NIGHT_SKY.zenith, day_sky.zenith, day),
+        horizon: mix(horizon, SUNSET_COLOUR, twilight * 0.6),
+    };
+
+    Daylight {
+        light_direction: light_direction.normalize(),
+        light_colour,
+        light_strength,
+        ambient_strength: NIGHT_AMBIENT + (DAY_AMBIENT - NIGHT_AMBIENT) * day,
+        sky,
+    }
+}
+
+// Same as GLSL's `smoothstep`
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], amount: f32) -> [f32; 3] {
+    [0, 1, 2].map(|channel| a[channel] * (1.0 - amount) + b[channel] * amount)
+}
@@ -1 +1,6 @@
-pub mod renderer;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod renderer;
+pub mod settings;
+pub mod sky;
+pub mod daylight;
+pub mod shadows;
\ No newline at end of file
@@ -1,26 +1,56 @@
 use std::fs::read_to_string;
 
-use cgmath::SquareMatrix;
-use cgmath::{Matrix4, Vector3};
+use cgmath::{EuclideanSpace, Matrix4, Point3, SquareMatrix, Vector3};
 use glium::glutin::surface::WindowSurface;
-use glium::index::NoIndices;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::index::{NoIndices, PrimitiveType};
+use glium::texture::{DepthFormat, DepthTexture2dArray, MipmapsOption};
+use glium::uniforms::{DepthTextureComparison, MagnifySamplerFilter, MinifySamplerFilter};
+use glium::vertex::EmptyVertexAttributes;
 use glium::Surface;
 use glium::uniform;
 
+use crate::camera::frustum::Frustum;
+use crate::constants::world::CHUNK_SIZE;
+use crate::graphics::texture_pack::TexturePack;
+
+use super::daylight::daylight;
+use super::settings::RenderSettings;
+use super::shadows::{cascade_splits, fit_light_space, frustum_slice_corners, perspective_depth_range, SHADOW_CASCADES};
+use super::sky::fog_distances;
 use crate::shapes::triangle::{create_triangle, TriangleVertex};
-use crate::{camera::camera::Camera, graphics::texture::create_texture, world::world::World};
+use crate::world::coords::ChunkPos;
+use crate::world::voxels::CHUNK_HEIGHT;
+use crate::{camera::camera::Camera, graphics::texture::create_texture_array, world::world::World};
+
+// What happened to the loaded chunks in one frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub chunks_drawn: usize,
+    // Chunks outside the camera's frustum, skipped without a draw call
+    pub chunks_culled: usize,
+    // Chunks in the frustum but hidden behind other chunks
+    pub chunks_occluded: usize,
+}
 
 pub struct Renderer<'a> {
     cube_program: glium::Program,
+    sky_program: glium::Program,
+    shadow_program: glium::Program,
     triangle_program: glium::Program,
-    textures: Vec<glium::Texture2d>,
+    // Pages of the block texture atlas, one layer per page
+    textures: glium::texture::Texture2dArray,
+    // Depth from the light, one layer per cascade
+    shadow_map: DepthTexture2dArray,
+    pub settings: RenderSettings,
     params: glium::DrawParameters<'a>,
+    shadow_params: glium::DrawParameters<'a>,
     triangle_vertex_buffer: glium::VertexBuffer<TriangleVertex>,
     triangle_indices: NoIndices,
 }
 
 impl Renderer<'_> {
-    pub fn new(display: &glium::Display<WindowSurface>) -> Self {
+    pub fn new(display: &glium::Display<WindowSurface>, texture_pack: &TexturePack, settings: RenderSettings) -> Self {
         // Load shaders
         let vertex_shader_src = read_to_string("res/shaders/cube_vertex.glsl").expect("failed to read vertex shader");
         let fragment_shader_src = read_to_string("res/shaders/cube_fragment.glsl").expect("failed to read fragment shader");
@@ -28,17 +58,31 @@ impl Renderer<'_> {
         let triangle_vertex_shader_src = read_to_string("res/shaders/triangle_vertex.glsl").expect("failed to read vertex shader");
         let triangle_fragment_shader_src = read_to_string("res/shaders/triangle_fragment.glsl").expect("failed to read fragment shader");
 
+        let sky_vertex_shader_src = read_to_string("res/shaders/sky_vertex.glsl").expect("failed to read vertex shader");
+        let sky_fragment_shader_src = read_to_string("res/shaders/sky_fragment.glsl").expect("failed to read fragment shader");
+
+        let shadow_vertex_shader_src = read_to_string("res/shaders/shadow_vertex.glsl").expect("failed to read vertex shader");
+        let shadow_fragment_shader_src = read_to_string("res/shaders/shadow_fragment.glsl").expect("failed to read fragment shader");
+
         let cube_program = glium::Program::from_source(display, &vertex_shader_src, &fragment_shader_src, None).unwrap();
+        let sky_program = glium::Program::from_source(display, &sky_vertex_shader_src, &sky_fragment_shader_src, None).unwrap();
+        let shadow_program = glium::Program::from_source(display, &shadow_vertex_shader_src, &shadow_fragment_shader_src, None).unwrap();
         let triangle_program = glium::Program::from_source(display, &triangle_vertex_shader_src, &triangle_fragment_shader_src, None).unwrap();
 
         // Initialize textures
-        let textures: Vec<glium::Texture2d> = vec![
-            create_texture(display, include_bytes!("../../res/blocks/dark-grass.png")),
-            create_texture(display, include_bytes!("../../res/blocks/light-grass.png")),
-            create_texture(display, include_bytes!("../../res/blocks/light-sand.png")),
-            create_texture(display, include_bytes!("../../res/blocks/rock-1.png")),
-            create_texture(display, include_bytes!("../../res/blocks/brown.png")),
-        ];
+        // Mipmaps are always made, so turning them on in the settings works without reloading the textures
+        let layout = texture_pack.manifest.layout();
+        let textures = create_texture_array(display, texture_pack.atlas_pages(), layout.mip_levels());
+
+        let shadow_map = DepthTexture2dArray::empty_with_format(
+            display,
+            DepthFormat::I24,
+            MipmapsOption::NoMipmap,
+            settings.shadow_map_size,
+            settings.shadow_map_size,
+            SHADOW_CASCADES as u32,
+        )
+        .expect("failed to create the shadow map");
 
         // Initialize draw parameters
         let params = glium::DrawParameters {
@@ -50,6 +94,17 @@ impl Renderer<'_> {
             ..Default::default()
         };
 
+        // Depths are pushed away from the light a little, so surfaces don't shadow themselves
+        let shadow_params = glium::DrawParameters {
+            polygon_offset: glium::draw_parameters::PolygonOffset {
+                factor: 2.0,
+                units: 2.0,
+                fill: true,
+                ..Default::default()
+            },
+            ..params.clone()
+        };
+
         // triangle
         let shape = create_triangle();
         let triangle_vertex_buffer = glium::VertexBuffer::new(display, &shape).unwrap();
@@ -57,25 +112,136 @@ impl Renderer<'_> {
 
         Renderer {
             cube_program,
+            sky_program,
+            shadow_program,
             textures,
+            shadow_map,
+            settings,
             params,
+            shadow_params,
             triangle_program,
             triangle_vertex_buffer,
             triangle_indices
         }
     }
 
-    pub fn render(&self, target: &mut glium::Frame, world: &World, camera: &Camera, perspective: Matrix4<f32>) {
+    // Draw the loaded chunks into every cascade of the shadow map, returning each cascade's light space
+    // and the view depths where they end
+    fn render_shadows(
+        &self,
+        display: &glium::Display<WindowSurface>,
+        world: &World,
+        camera: &Camera,
+        perspective: Matrix4<f32>,
+        light_direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> ([Matrix4<f32>; SHADOW_CASCADES], Vec<f32>) {
+        let view = camera.get_view_matrix();
+        let (near, far) = perspective_depth_range(perspective);
+        let splits = cascade_splits(near, far.min(max_distance), SHADOW_CASCADES);
+        let mut light_spaces = [Matrix4::identity(); SHADOW_CASCADES];
+
+        let mut start = near;
+        for (cascade, &end) in splits.iter().enumerate() {
+            let corners = frustum_slice_corners(perspective, view, start, end);
+            // Anything up to the top of the world can cast a shadow into the cascade
+            let light_space =
+                fit_light_space(&corners, light_direction, camera.position, self.settings.shadow_map_size, CHUNK_HEIGHT as f32);
+            let light_frustum = Frustum::from_matrix(light_space);
+
+            let layer = self.shadow_map.main_level().layer(cascade as u32).unwrap();
+            let mut framebuffer = SimpleFrameBuffer::depth_only(display, layer).unwrap();
+            framebuffer.clear_depth(1.0);
+
+            for chunk in world.chunks.values() {
+                let (min, max) = chunk_bounds(camera, chunk.position);
+                if !light_frustum.intersects_aabb(min, max) {
+                    continue;
+                }
+
+                let model = camera.model_matrix(chunk.position.world_origin());
+                framebuffer
+                    .draw(
+                        &chunk.vertex_buffer,
+                        &chunk.index_buffer,
+                        &self.shadow_program,
+                        &uniform! {
+                            model: Into::<[[f32; 4]; 4]>::into(model),
+                            light_space: Into::<[[f32; 4]; 4]>::into(light_space),
+                        },
+                        &self.shadow_params,
+                    )
+                    .unwrap();
+            }
+
+            light_spaces[cascade] = light_space;
+            start = end;
+        }
+
+        (light_spaces, splits)
+    }
+
+    pub fn render(
+        &self,
+        display: &glium::Display<WindowSurface>,
+        target: &mut glium::Frame,
+        world: &World,
+        camera: &Camera,
+        perspective: Matrix4<f32>,
+    ) -> RenderStats {
         let view = camera.get_view_matrix();
-        let light = [-1.0, 0.4, 0.9f32];
+        let daylight = daylight(world.clock.time_of_day());
+        let light: [f32; 3] = daylight.light_direction.into();
+        let frustum = camera.frustum(perspective);
+        let visible_chunks = world.visible_chunks(ChunkPos::column_containing(camera.position));
+        let mut stats = RenderStats::default();
+        let (fog_start, fog_end) = fog_distances(world.render_distance);
+
+        // Nothing casts a shadow when there's no light to block
+        let shadows = self.settings.shadows && daylight.light_strength > 0.0;
+        let (light_spaces, cascade_ends) = if shadows {
+            let max_distance = fog_end.min(self.settings.shadow_distance as f32);
+            self.render_shadows(display, world, camera, perspective, daylight.light_direction, max_distance)
+        } else {
+            ([Matrix4::identity(); SHADOW_CASCADES], vec![0.0; SHADOW_CASCADES])
+        };
+        let shadow_map = self
+            .shadow_map
+            .sampled()
+            .magnify_filter(MagnifySamplerFilter::Linear)
+            .minify_filter(MinifySamplerFilter::Linear)
+            .depth_texture_comparison(Some(DepthTextureComparison::LessOrEqual));
+
+        // The sky goes behind everything, without touching the depth buffer
+        let inverse_view_projection = (perspective * view).invert().expect("view projection can't be inverted");
+        target
+            .draw(
+                EmptyVertexAttributes { len: 3 },
+                NoIndices(PrimitiveType::TrianglesList),
+                &self.sky_program,
+                &uniform! {
+                    inverse_view_projection: Into::<[[f32; 4]; 4]>::into(inverse_view_projection),
+                    u_sky_zenith: daylight.sky.zenith,
+                    u_sky_horizon: daylight.sky.horizon,
+                },
+                &Default::default(),
+            )
+            .unwrap();
 
-        for chunk in &world.chunks {
-            let position_vector = Vector3::new(
-                chunk.position.x as f32 * world.chunk_size as f32,
-                chunk.position.y as f32 * world.chunk_size as f32,
-                chunk.position.z as f32 * world.chunk_size as f32
-            );
-            let model = Matrix4::from_translation(position_vector);
+        for chunk in world.chunks.values() {
+            let (min, max) = chunk_bounds(camera, chunk.position);
+            if !frustum.intersects_aabb(min, max) {
+                stats.chunks_culled += 1;
+                continue;
+            }
+            if visible_chunks.as_ref().is_some_and(|visible| !visible.contains(&chunk.position)) {
+                stats.chunks_occluded += 1;
+                continue;
+            }
+            stats.chunks_drawn += 1;
+
+            // Relative to the camera, so chunks far from spawn don't jitter
+            let model = camera.model_matrix(chunk.position.world_origin());
 
             target
                 .draw(
@@ -87,23 +253,37 @@ impl Renderer<'_> {
                         view: Into::<[[f32; 4]; 4]>::into(view),
                         perspective: Into::<[[f32; 4]; 4]>::into(perspective),
                         u_light: light,
-                        tex0: self.textures[0].sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                        tex1: self.textures[1].sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                        tex2: self.textures[2].sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                        tex3: self.textures[3].sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                        tex4: self.textures[4].sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                    },
+                        u_light_colour: daylight.light_colour,
+                        u_light_strength: daylight.light_strength,
+                        u_ambient_strength: daylight.ambient_strength,
+                        u_sky_zenith: daylight.sky.zenith,
+                        u_sky_horizon: daylight.sky.horizon,
+                        u_fog_start: fog_start,
+                        u_fog_end: fog_end,
+                        u_shadows: shadows,
+                        u_shadow_map: shadow_map,
+                        u_cascade_ends: [cascade_ends[0], cascade_ends[1], cascade_ends[2]],
+                        textures: self
+                            .textures
+                            .sampled()
+                            .magnify_filter(self.settings.magnify_sampler_filter())
+                            .minify_filter(self.settings.minify_sampler_filter())
+                            .anisotropy(self.settings.anisotropy),
+                    }
+                    .add("u_light_spaces[0]", Into::<[[f32; 4]; 4]>::into(light_spaces[0]))
+                    .add("u_light_spaces[1]", Into::<[[f32; 4]; 4]>::into(light_spaces[1]))
+                    .add("u_light_spaces[2]", Into::<[[f32; 4]; 4]>::into(light_spaces[2])),
                     &self.params,
                 )
                 .unwrap();
         }
         
         // draw triangle
-        let triangle_model = Matrix4::from_translation(Vector3::new(2.0, 20.0, 5.0)); // Example transformation
+        let triangle_model = camera.model_matrix(Point3::new(2.0, 20.0, 5.0)); // Example transformation
 
 target.draw(
     &self.triangle_vertex_buffer,
-    &self.triangle_indices,
+    self.triangle_indices,
     &self.triangle_program,
     &uniform! {
         model: Into::<[[f32; 4]; 4]>::into(triangle_model),
@@ -112,5 +292,15 @@ target.draw(
     },
     &self.params,
 ).unwrap();
+
+        stats
     }
-}
\ No newline at end of file
+}
+
+// Box around a chunk's blocks, relative to the camera. Blocks are centred on whole coordinates,
+// so the box starts half a block before the chunk's origin.
+fn chunk_bounds(camera: &Camera, position: ChunkPos) -> (Point3<f32>, Point3<f32>) {
+    let min = Point3::from_vec(camera.relative_position(position.world_origin())) - Vector3::new(0.5, 0.5, 0.5);
+    let max = min + Vector3::new(CHUNK_SIZE as f32, CHUNK_HEIGHT as f32, CHUNK_SIZE as f32);
+    (min, max)
+}
@@ -0,0 +1,112 @@
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter};
+
+// How a texture is sampled between its texels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+// Ready-made combinations of the texture filters, from blocky to smooth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFiltering {
+    // Every texel stays a hard edged square, far away too
+    Nearest,
+    // Smaller mipmaps in the distance, so far textures don't shimmer
+    Mipmapped,
+    // Mipmaps and anisotropic filtering, surfaces seen at a steep angle stay sharp
+    Anisotropic,
+}
+
+impl TextureFiltering {
+    pub const ALL: [TextureFiltering; 3] = [TextureFiltering::Nearest, TextureFiltering::Mipmapped, TextureFiltering::Anisotropic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextureFiltering::Nearest => "nearest",
+            TextureFiltering::Mipmapped => "mipmapped",
+            TextureFiltering::Anisotropic => "anisotropic",
+        }
+    }
+
+    // The one after this, wrapping around to the first
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&filtering| filtering == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+// Options for how the world is drawn, can be changed between frames
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderSettings {
+    // Filter for textures drawn larger than they are, nearest keeps the pixel art crisp
+    pub magnify_filter: TextureFilter,
+    // Filter for textures drawn smaller than they are
+    pub minify_filter: TextureFilter,
+    // Filter between mipmap levels, `None` samples the full size texture even far away
+    pub mipmap_filter: Option<TextureFilter>,
+    // Samples taken along surfaces seen at a steep angle, 1 turns anisotropic filtering off
+    pub anisotropy: u16,
+    // Shadows cast by the sun and moon
+    pub shadows: bool,
+    // Width and height of each shadow cascade, only read when the renderer is created
+    pub shadow_map_size: u32,
+    // Blocks from the camera shadows reach out to, never past the fog
+    pub shadow_distance: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            magnify_filter: TextureFilter::Nearest,
+            minify_filter: TextureFilter::Linear,
+            mipmap_filter: Some(TextureFilter::Linear),
+            anisotropy: 4,
+            shadows: true,
+            shadow_map_size: 2048,
+            shadow_distance: 128,
+        }
+    }
+}
+
+impl RenderSettings {
+    // The combination the texture filters are set to, if they match one
+    pub fn texture_filtering(&self) -> Option<TextureFiltering> {
+        TextureFiltering::ALL.into_iter().find(|&filtering| {
+            let mut settings = *self;
+            settings.set_texture_filtering(filtering);
+            settings == *self
+        })
+    }
+
+    pub fn set_texture_filtering(&mut self, filtering: TextureFiltering) {
+        use TextureFilter::{Linear, Nearest};
+
+        self.magnify_filter = Nearest;
+        (self.minify_filter, self.mipmap_filter, self.anisotropy) = match filtering {
+            TextureFiltering::Nearest => (Nearest, None, 1),
+            TextureFiltering::Mipmapped => (Linear, Some(Linear), 1),
+            TextureFiltering::Anisotropic => (Linear, Some(Linear), 4),
+        };
+    }
+
+    pub fn magnify_sampler_filter(&self) -> MagnifySamplerFilter {
+        match self.magnify_filter {
+            TextureFilter::Nearest => MagnifySamplerFilter::Nearest,
+            TextureFilter::Linear => MagnifySamplerFilter::Linear,
+        }
+    }
+
+    pub fn minify_sampler_filter(&self) -> MinifySamplerFilter {
+        use TextureFilter::{Linear, Nearest};
+
+        match (self.minify_filter, self.mipmap_filter) {
+            (Nearest, None) => MinifySamplerFilter::Nearest,
+            (Linear, None) => MinifySamplerFilter::Linear,
+            (Nearest, Some(Nearest)) => MinifySamplerFilter::NearestMipmapNearest,
+            (Linear, Some(Nearest)) => MinifySamplerFilter::LinearMipmapNearest,
+            (Nearest, Some(Linear)) => MinifySamplerFilter::NearestMipmapLinear,
+            (Linear, Some(Linear)) => MinifySamplerFilter::LinearMipmapLinear,
+        }
+    }
+}
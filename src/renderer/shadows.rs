@@ -0,0 +1,88 @@
+use cgmath::{ortho, EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+// Shadow map cascades, `cube_fragment.glsl` has the same number
+pub const SHADOW_CASCADES: usize = 3;
+
+// Blend between evenly spaced cascades and ones spaced by the same ratio, more of the second
+// keeps the cascades near the camera small and sharp
+const SPLIT_BLEND: f32 = 0.75;
+
+// Near and far distances of a projection made by `cgmath::perspective`
+pub fn perspective_depth_range(perspective: Matrix4<f32>) -> (f32, f32) {
+    let (a, b) = (perspective.z.z, perspective.w.z);
+    (b / (a - 1.0), b / (a + 1.0))
+}
+
+// Distances from the camera where each cascade ends, the first starts at `near` and each next one
+// where the one before it ends. The last one always ends at `far`.
+pub fn cascade_splits(near: f32, far: f32, cascades: usize) -> Vec<f32> {
+    (1..=cascades)
+        .map(|cascade| {
+            let fraction = cascade as f32 / cascades as f32;
+            let even = near + (far - near) * fraction;
+            let ratio = near * (far / near).powf(fraction);
+            ratio * SPLIT_BLEND + even * (1.0 - SPLIT_BLEND)
+        })
+        .collect()
+}
+
+// Corners of the part of the view frustum from `near` to `far` away from the camera, in camera relative world space.
+// `view` only rotates, the camera sits at the origin.
+pub fn frustum_slice_corners(perspective: Matrix4<f32>, view: Matrix4<f32>, near: f32, far: f32) -> [Vector3<f32>; 8] {
+    let inverse = (perspective * view).invert().expect("view projection can't be inverted");
+    let mut corners = [Vector3::new(0.0, 0.0, 0.0); 8];
+
+    for (index, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].into_iter().enumerate() {
+        // A ray from the camera through a corner of the screen, scaled to each distance along the view direction
+        let point = inverse * Vector4::new(x, y, 1.0, 1.0);
+        let corner = point.truncate() / point.w;
+        let depth = -(view * corner.extend(0.0)).z;
+        corners[index] = corner * (near / depth);
+        corners[index + 4] = corner * (far / depth);
+    }
+
+    corners
+}
+
+// Orthographic projection from the light covering the corners, pointing along `-light_direction`.
+// Its texels are lined up with whole multiples of their size in the world, not relative to the camera,
+// so shadow edges don't crawl as the camera moves. Anything up to `caster_distance` further towards the
+// light still casts shadows into it.
+pub fn fit_light_space(
+    corners: &[Vector3<f32>; 8],
+    light_direction: Vector3<f32>,
+    camera_position: Point3<f64>,
+    map_size: u32,
+    caster_distance: f32,
+) -> Matrix4<f32> {
+    let light_direction = light_direction.normalize();
+    let up = if light_direction.y.abs() > 0.99 { Vector3::unit_z() } else { Vector3::unit_y() };
+    let light_view = Matrix4::look_to_rh(Point3::origin(), -light_direction, up);
+
+    // A sphere around the slice keeps the projection the same size however the camera turns
+    let center = corners.iter().sum::<Vector3<f32>>() / 8.0;
+    let radius = corners.iter().map(|corner| (corner - center).magnitude()).fold(0.0, f32::max).ceil();
+    // Snapping moves the projection by up to a texel, which a texel of margin on every side leaves room for
+    let texel = 2.0 * radius / (map_size - 2) as f32;
+    let half_size = radius + texel;
+
+    // Snapped in world coordinates, which only f64 has the precision for far from spawn
+    let camera = light_view.cast::<f64>().unwrap() * camera_position.to_vec().extend(0.0);
+    let center = (light_view * center.extend(0.0)).truncate();
+    let snap = |camera: f64, center: f32| {
+        let texel = texel as f64;
+        (((camera + center as f64) / texel).floor() * texel - camera) as f32
+    };
+    let (x, y) = (snap(camera.x, center.x), snap(camera.y, center.y));
+
+    // The light looks down -z, so the near plane is the one closer to the light
+    let projection = ortho(
+        x - half_size,
+        x + half_size,
+        y - half_size,
+        y + half_size,
+        -center.z - radius - caster_distance,
+        -center.z + radius,
+    );
+    projection * light_view
+}
@@ -0,0 +1,37 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::constants::world::CHUNK_SIZE;
+use crate::world::render_distance::RenderDistance;
+
+// Colours of the sky, blended from the horizon up to straight overhead
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyColours {
+    pub zenith: [f32; 3],
+    pub horizon: [f32; 3],
+}
+
+impl Default for SkyColours {
+    fn default() -> Self {
+        SkyColours {
+            zenith: [0.094, 0.271, 0.961],
+            horizon: [0.62, 0.78, 0.96],
+        }
+    }
+}
+
+impl SkyColours {
+    // Colour of the sky looking in a direction, the same gradient `sky_fragment.glsl` draws.
+    // Below the horizon it stays the horizon colour, so fogged terrain blends into it.
+    pub fn colour(&self, direction: Vector3<f32>) -> [f32; 3] {
+        let height = direction.normalize().y.max(0.0).sqrt();
+        [0, 1, 2].map(|channel| self.horizon[channel] * (1.0 - height) + self.zenith[channel] * height)
+    }
+}
+
+// Distances in blocks where fog starts and where terrain has completely faded into the sky.
+// The fog is thick by the edge of the loaded chunks, so chunks streaming in there can't be seen appearing.
+pub fn fog_distances(render_distance: RenderDistance) -> (f32, f32) {
+    // The camera can be up to a chunk away from the centre of its chunk the load radius is measured from
+    let end = ((render_distance.load_radius - 1).max(1) * CHUNK_SIZE) as f32;
+    (end * 0.6, end)
+}
@@ -1,8 +1,8 @@
 use cgmath::Vector3;
 
-use glium::{glutin::surface::WindowSurface, implement_vertex, index::PrimitiveType, Display, IndexBuffer, VertexBuffer};
-
-use crate::{graphics::cube::Vertex, world::world::World};
+use crate::graphics::atlas::TileUVs;
+use crate::world::light::{Light, MAX_LIGHT};
+use crate::graphics::cube::{Vertex, FULL_LIGHT};
 
 // #[derive(Copy, Clone)]
 // pub struct Vertex {
@@ -13,46 +13,48 @@ use crate::{graphics::cube::Vertex, world::world::World};
 // }
 // implement_vertex!(Vertex, position, normal, tex_coords, texture_id);
 
+const CUBE_VERTICES: [Vertex; 24] = [
+    // Front face
+    Vertex { position: [-0.5, -0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5, -0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5,  0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+
+    // Back face
+    Vertex { position: [-0.5, -0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5, -0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5,  0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+
+    // Top face
+    Vertex { position: [-0.5,  0.5, -0.5], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5, -0.5], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5,  0.5], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5,  0.5,  0.5], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+
+    // Bottom face
+    Vertex { position: [-0.5, -0.5, -0.5], normal: [0.0, -1.0, 0.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5, -0.5, -0.5], normal: [0.0, -1.0, 0.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5, -0.5,  0.5], normal: [0.0, -1.0, 0.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5, -0.5,  0.5], normal: [0.0, -1.0, 0.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+
+    // Right face
+    Vertex { position: [ 0.5, -0.5, -0.5], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5, -0.5,  0.5], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5,  0.5], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [ 0.5,  0.5, -0.5], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+
+    // Left face
+    Vertex { position: [-0.5, -0.5, -0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [1.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5, -0.5,  0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [0.0, 0.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5,  0.5,  0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [0.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+    Vertex { position: [-0.5,  0.5, -0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [1.0, 1.0], texture_id: 0, light: FULL_LIGHT },
+];
+
 pub fn create_cube(world_pos: Vector3<f32>, texture_id: u32) -> (Vec<Vertex>, Vec<u32>) {
     // let mut world = World::new(&display);
 
-    let vertices: [Vertex; 24] = [
-         // Front face
-        Vertex { position: [-0.5, -0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [ 0.5, -0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [ 0.5,  0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [1.0, 1.0], texture_id },
-        Vertex { position: [-0.5,  0.5,  0.5], normal: [0.0, 0.0, 1.0], tex_coords: [0.0, 1.0], texture_id },
-        
-        // Back face
-        Vertex { position: [-0.5, -0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [ 0.5, -0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [ 0.5,  0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [0.0, 1.0], texture_id },
-        Vertex { position: [-0.5,  0.5, -0.5], normal: [0.0, 0.0, -1.0], tex_coords: [1.0, 1.0], texture_id },
-    
-        // Top face
-        Vertex { position: [-0.5,  0.5, -0.5], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [ 0.5,  0.5, -0.5], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [ 0.5,  0.5,  0.5], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 1.0], texture_id },
-        Vertex { position: [-0.5,  0.5,  0.5], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 1.0], texture_id },
-    
-        // Bottom face
-        Vertex { position: [-0.5, -0.5, -0.5], normal: [0.0, -1.0, 0.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [ 0.5, -0.5, -0.5], normal: [0.0, -1.0, 0.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [ 0.5, -0.5,  0.5], normal: [0.0, -1.0, 0.0], tex_coords: [0.0, 1.0], texture_id },
-        Vertex { position: [-0.5, -0.5,  0.5], normal: [0.0, -1.0, 0.0], tex_coords: [1.0, 1.0], texture_id },
-    
-        // Right face
-        Vertex { position: [ 0.5, -0.5, -0.5], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [ 0.5, -0.5,  0.5], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [ 0.5,  0.5,  0.5], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 1.0], texture_id },
-        Vertex { position: [ 0.5,  0.5, -0.5], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 1.0], texture_id },
-    
-        // Left face
-        Vertex { position: [-0.5, -0.5, -0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [1.0, 0.0], texture_id },
-        Vertex { position: [-0.5, -0.5,  0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [0.0, 0.0], texture_id },
-        Vertex { position: [-0.5,  0.5,  0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [0.0, 1.0], texture_id },
-        Vertex { position: [-0.5,  0.5, -0.5], normal: [-1.0, 0.0, 0.0], tex_coords: [1.0, 1.0], texture_id },
-        ];
+    let vertices = CUBE_VERTICES.map(|v| Vertex { texture_id, ..v });
 
         let indices: [u32; 36] = [
             0,  1,  2,  2,  3,  0, // front
@@ -75,8 +77,35 @@ pub fn create_cube(world_pos: Vector3<f32>, texture_id: u32) -> (Vec<Vertex>, Ve
          normal: v.normal,
          tex_coords: v.tex_coords,
          texture_id: v.texture_id,
+         light: v.light,
         })
        .collect();
 
       (transformed_vertices, indices.to_vec())
-}
\ No newline at end of file
+}
+
+// The 4 vertices of one face of a cube, faces are in the same order as `CUBE_VERTICES`.
+// The face's texture coordinates are mapped onto its tile of the atlas.
+pub fn create_cube_face(world_pos: Vector3<f32>, face: usize, uvs: TileUVs, light: Light) -> [Vertex; 4] {
+    let light = [light.sun as f32 / MAX_LIGHT as f32, light.block as f32 / MAX_LIGHT as f32];
+    let mut vertices = [CUBE_VERTICES[0]; 4];
+    let [(u1, v1), (u2, _), _, (_, v2)] = uvs.corners;
+
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let v = CUBE_VERTICES[face * 4 + i];
+        let [s, t] = v.tex_coords;
+        *vertex = Vertex {
+            position: [
+                v.position[0] + world_pos.x,
+                v.position[1] + world_pos.y,
+                v.position[2] + world_pos.z,
+            ],
+            tex_coords: [u1 + s * (u2 - u1), v1 + t * (v2 - v1)],
+            texture_id: uvs.layer,
+            light,
+            ..v
+        };
+    }
+
+    vertices
+}
@@ -12,6 +12,6 @@ pub fn create_triangle() -> Vec<TriangleVertex> {
     let vertex2 = TriangleVertex { position: [ 0.0,  0.5] };
     let vertex3 = TriangleVertex { position: [ 0.5, -0.25] };
     let shape = vec![vertex1, vertex2, vertex3];
-    
-    return shape;
+
+    shape
 }
\ No newline at end of file
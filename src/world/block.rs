@@ -0,0 +1,81 @@
+// Every kind of block a chunk can hold
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Block {
+    #[default]
+    Air,
+    DarkGrass,
+    LightGrass,
+    Sand,
+    Rock,
+    Dirt,
+    Wood,
+    Water,
+    Path,
+    Torch,
+    Lava,
+}
+
+impl Block {
+    // Every block, in the order of their ids
+    pub const ALL: [Block; 11] = [
+        Block::Air,
+        Block::DarkGrass,
+        Block::LightGrass,
+        Block::Sand,
+        Block::Rock,
+        Block::Dirt,
+        Block::Wood,
+        Block::Water,
+        Block::Path,
+        Block::Torch,
+        Block::Lava,
+    ];
+
+    // Name of the block in texture pack manifests
+    pub fn name(self) -> &'static str {
+        match self {
+            Block::Air => "air",
+            Block::DarkGrass => "dark-grass",
+            Block::LightGrass => "light-grass",
+            Block::Sand => "sand",
+            Block::Rock => "rock",
+            Block::Dirt => "dirt",
+            Block::Wood => "wood",
+            Block::Water => "water",
+            Block::Path => "path",
+            Block::Torch => "torch",
+            Block::Lava => "lava",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|block| block.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_air(self) -> bool {
+        self == Block::Air
+    }
+
+    // Whether the faces of neighbouring blocks are hidden behind this one
+    pub fn is_opaque(self) -> bool {
+        !matches!(self, Block::Air | Block::Water)
+    }
+
+    // Block light the block gives off, up to `MAX_LIGHT`
+    pub fn light_emission(self) -> u8 {
+        match self {
+            Block::Torch => 14,
+            Block::Lava => 15,
+            _ => 0,
+        }
+    }
+
+    // Light lost passing through the block on top of the level every step loses, opaque blocks stop it completely
+    pub fn light_absorption(self) -> u8 {
+        match self {
+            Block::Water => 2,
+            _ => 0,
+        }
+    }
+}
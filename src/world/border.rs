@@ -0,0 +1,72 @@
+use crate::constants::world::CHUNK_SIZE;
+
+use super::block::Block;
+use super::coords::ChunkPos;
+use super::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+// Something of every block just outside a chunk's four sides, copied from the loaded chunks next to it,
+// so the chunk can be meshed away from the world
+#[derive(Clone)]
+pub struct ChunkBorder<T> {
+    // The +x, -x, +z and -z sides, `None` where no chunk is loaded
+    sides: [Option<Vec<T>>; 4],
+}
+
+// Blocks just outside a chunk, for culling the faces its border blocks share with the neighbours
+pub type BorderBlocks = ChunkBorder<Block>;
+
+impl<T> Default for ChunkBorder<T> {
+    fn default() -> Self {
+        ChunkBorder { sides: [None, None, None, None] }
+    }
+}
+
+impl<T: Copy> ChunkBorder<T> {
+    const SIDES: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+    // `neighbour` finds a loaded chunk and `get` reads a block of it, in that chunk's local coordinates
+    pub fn copy_from<C>(chunk: ChunkPos, neighbour: impl Fn(ChunkPos) -> Option<C>, get: impl Fn(&C, i32, i32, i32) -> T) -> Self {
+        let sides = Self::SIDES.map(|(dx, dz)| {
+            let neighbour = neighbour(chunk.offset(dx, 0, dz))?;
+            // The neighbour's layer of blocks touching this chunk
+            let layer = (0..CHUNK_SIZE).flat_map(|along| {
+                let (x, z) = match (dx, dz) {
+                    (1, 0) => (0, along),
+                    (-1, 0) => (CHUNK_SIZE - 1, along),
+                    (0, 1) => (along, 0),
+                    _ => (along, CHUNK_SIZE - 1),
+                };
+                (0..CHUNK_HEIGHT).map(move |y| (x, y, z))
+            });
+            Some(layer.map(|(x, y, z)| get(&neighbour, x, y, z)).collect())
+        });
+        ChunkBorder { sides }
+    }
+
+    // A block next to one of the chunk's sides, in the chunk's local coordinates.
+    // `None` inside the chunk, above or below it, and on sides without a loaded chunk.
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Option<T> {
+        if !(0..CHUNK_HEIGHT).contains(&y) {
+            return None;
+        }
+        let inside = 0..CHUNK_SIZE;
+        let (side, along) = if x == CHUNK_SIZE && inside.contains(&z) {
+            (0, z)
+        } else if x == -1 && inside.contains(&z) {
+            (1, z)
+        } else if z == CHUNK_SIZE && inside.contains(&x) {
+            (2, x)
+        } else if z == -1 && inside.contains(&x) {
+            (3, x)
+        } else {
+            return None;
+        };
+        self.sides[side].as_ref().map(|layer| layer[(along * CHUNK_HEIGHT + y) as usize])
+    }
+}
+
+impl BorderBlocks {
+    pub fn gather<'a>(chunk: ChunkPos, voxels: impl Fn(ChunkPos) -> Option<&'a ChunkVoxels>) -> Self {
+        Self::copy_from(chunk, voxels, |voxels, x, y, z| voxels.get(x, y, z))
+    }
+}
@@ -1,15 +1,15 @@
-use std::collections::HashMap;
-
-use cgmath::{Point3, Vector3};
-use glium::{glutin::surface::WindowSurface, uniforms::Sampler};
+use cgmath::Point3;
+use glium::glutin::surface::WindowSurface;
 use noise::{NoiseFn, Perlin};
 
-use crate::graphics::cube::{create_single_tx_cube_vertices, FaceUVs, Vertex};
-
-use super::terrain::{generate_arch_mountain_terrain, generate_flat_terrain, generate_mountainous_terrain, generate_spiral_mountain_terrain, generate_terrain_chunk, generate_trees};
+use crate::graphics::cube::Vertex;
 
-const CHUNK_SIZE: i32 = 16;
-const OVERLAP: i32 = 1; // Amount of overlap with neighboring chunks
+use super::coords::ChunkPos;
+use super::generator::GeneratorConfig;
+use super::light::ChunkLight;
+use super::mesher::mesh_chunk;
+use super::visibility::ChunkVisibility;
+use super::voxels::ChunkVoxels;
 
 pub enum Biome {
     Plains,
@@ -20,125 +20,78 @@ pub enum Biome {
     // Tundra,
 }
 
-
 pub struct ChunkData {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    // Which faces of the chunk can see each other, for skipping chunks hidden behind others
+    pub visibility: ChunkVisibility,
 }
 
-pub fn generate_chunk(chunk_position: Point3<i32>, flat_height: i32) -> ChunkData {
+pub fn generate_chunk(chunk_position: Point3<i32>, config: &GeneratorConfig) -> ChunkVoxels {
     let biome = generate_biome_for_chunk(chunk_position);
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+    let mut voxels = ChunkVoxels::new();
 
-    let seed: u32 = 142;
-    let perlin = Perlin::new(seed);
+    match biome {
+        Biome::Plains => {
+            // Run every pass of the world's generator in order
+            for (pass, noise) in config.passes.iter().zip(config.noise_layers()) {
+                pass.generate(chunk_position, config, noise, &mut voxels);
+            }
+        }
+        Biome::Mountains | Biome::Desert => ()
+    }
+    
+    voxels
+}
 
-    let scale = 0.1; // Adjust the scale for frequency of mountains
-    let height_scale = 1.0; // Taller mountains
+// A chunk generated and meshed on the CPU, waiting for its buffers to be uploaded
+pub struct GeneratedChunk {
+    pub position: ChunkPos,
+    pub voxels: ChunkVoxels,
+    // The chunk's own light, without any from the chunks around it
+    pub light: ChunkLight,
+    pub chunk_data: ChunkData,
+}
 
-    // Generate an extended height map for mountainous terrain
-    let extended_size = CHUNK_SIZE + 2 * OVERLAP;
+impl GeneratedChunk {
+    // Everything but the upload, safe to run off the render thread
+    pub fn generate(position: ChunkPos, generator: &GeneratorConfig) -> Self {
+        let voxels = generate_chunk(position.into(), generator); // Generate chunk with the world's generator passes
+        Self::from_voxels(position, voxels)
+    }
 
-    let mut extended_height_map: Vec<Vec<f64>> = vec![vec![0.0; extended_size as usize]; extended_size as usize];
+    // Light and mesh a chunk whose blocks are already known
+    pub fn from_voxels(position: ChunkPos, voxels: ChunkVoxels) -> Self {
+        let light = ChunkLight::compute(&voxels);
+        Self::with_light(position, voxels, light)
+    }
 
-     // Generate the flat base layer
-     generate_flat_terrain(flat_height, &mut vertices, &mut indices, 4);
+    // Mesh a chunk whose blocks and light are already known
+    pub fn with_light(position: ChunkPos, voxels: ChunkVoxels, light: ChunkLight) -> Self {
+        let chunk_data = mesh_chunk(&voxels, &light);
 
-       // Generate the mountainous terrain
-    // generate_mountainous_terrain(chunk_position, flat_height, &mut vertices, &mut indices, 2,  perlin, scale, height_scale, extended_size, 0);
-    match biome {
-        Biome::Plains => {
-        //    generate_flat_terrain(flat_height + 1, &mut vertices, &mut indices, 0);
-          generate_terrain_chunk(
-           chunk_position, 
-           &mut vertices,  
-           &mut indices,
-           &perlin,       
-        0.01,          
-    10.0,          
-    1,              
-    0,              
-);
-
-// generate_arch_mountain_terrain(chunk_position, flat_height, &mut vertices, &mut indices,  2, 0.01, 0.0, extended_size, 0, 4, 10.0, 1);
-// generate_spiral_mountain_terrain(chunk_position, flat_height, &mut vertices, &mut indices, 2, 0.01, 3.0, extended_size, 0, 3,  5.0, 1.0);
-        generate_trees(
-            chunk_position,
-            flat_height,
-            &mut vertices,
-            &mut indices,
-            &perlin,
-            0.1, // tree density (adjust as needed)
-            13,    // tree height
-            4,
-            0
-        );
-
-        generate_mountainous_terrain(chunk_position, flat_height, &mut vertices, &mut indices,  perlin, 0.01, 60.0, extended_size, 0, 4, 10);
-       
+        GeneratedChunk {
+            position,
+            voxels,
+            light,
+            chunk_data,
         }
-        // Biome::Mountains => {
-        //     generate_flat_terrain(flat_height + 1, &mut vertices, &mut indices, 0);
-        //     generate_terrain_chunk(
-        //         chunk_position, // Chunk position in the world
-        //         &mut vertices,  // Vertex buffer to store the chunk vertices
-        //         &mut indices,   // Index buffer to store the chunk indices
-        //         &perlin,        // Perlin noise instance
-        //         0.01,            // Lower noise scale for more detailed hills
-        //         10.0,           // Higher height scale for more dramatic height differences
-        //         1,              // Higher base height to lift the terrain off the ground more
-        //         0,              // Texture ID for terrain blocks
-        //     );
-        //     generate_trees(
-        //         chunk_position,
-        //         flat_height,
-        //         &mut vertices,
-        //         &mut indices,
-        //         &perlin,
-        //         0.1, // tree density (adjust as needed)
-        //         13,    // tree height
-        //         4,
-        //         0
-        //     );
-        //     generate_mountainous_terrain(chunk_position, flat_height, &mut vertices, &mut indices,  perlin, 0.03, 60.0, extended_size, 0, 3, 10);
-        // }    
-        // Biome::Desert => {
-        //       // Generate the mountainous terrain
-        //     generate_flat_terrain(flat_height + 1, &mut vertices, &mut indices, 2);
-        //     generate_terrain_chunk(
-        //         chunk_position, // Chunk position in the world
-        //         &mut vertices,  // Vertex buffer to store the chunk vertices
-        //         &mut indices,   // Index buffer to store the chunk indices
-        //         &perlin,        // Perlin noise instance
-        //         0.01,            // Lower noise scale for more detailed hills
-        //         10.0,           // Higher height scale for more dramatic height differences
-        //         1,              // Higher base height to lift the terrain off the ground more
-        //         2,              // Texture ID for terrain blocks
-        //     );
-        //     generate_mountainous_terrain(chunk_position, flat_height, &mut vertices, &mut indices,  perlin, 0.02, 20.0, extended_size, 2, 2, 2);
-        // }
-
-        _ => ()
-    }
-    
-    ChunkData {
-        indices,
-        vertices,
     }
 }
 
 pub struct Chunk {
    pub vertex_buffer: glium::VertexBuffer<Vertex>,
    pub index_buffer: glium::IndexBuffer<u32>,
-   pub position: Point3<i32>, // Chunk position in world space
+   pub position: ChunkPos,
+   pub voxels: ChunkVoxels,
+   pub light: ChunkLight,
    pub chunk_data: ChunkData,
 }
 
 impl Chunk {
-    pub fn new(display: &glium::Display<WindowSurface>, position: Point3<i32>) -> Self {
-        let flat_height = 0; // Define a flat terrain height
-        let chunk_data = generate_chunk(position, flat_height); // Generate chunk with both flat terrain and mountains
+    // Create the GPU buffers of a generated chunk, has to happen on the render thread
+    pub fn upload(display: &glium::Display<WindowSurface>, generated: GeneratedChunk) -> Self {
+        let GeneratedChunk { position, voxels, light, chunk_data } = generated;
 
         // create vertex and index buffer we got from chunk data struct
         let vertex_buffer = glium::VertexBuffer::new(display, &chunk_data.vertices).unwrap();
@@ -153,10 +106,26 @@ impl Chunk {
             vertex_buffer,
             index_buffer,
             position,
+            voxels,
+            light,
             chunk_data
         }
-    }}
+    }
+
+    // Swap in a new mesh after the chunk's blocks or light changed
+    pub fn replace_mesh(&mut self, display: &glium::Display<WindowSurface>, chunk_data: ChunkData) {
+        self.chunk_data = chunk_data;
+        self.vertex_buffer = glium::VertexBuffer::new(display, &self.chunk_data.vertices).unwrap();
+        self.index_buffer = glium::IndexBuffer::new(
+            display,
+            glium::index::PrimitiveType::TrianglesList,
+            &self.chunk_data.indices,
+        )
+        .unwrap();
+    }
+}
 
+#[allow(clippy::if_same_then_else)]
 pub fn generate_biome_for_chunk(chunk_position: Point3<i32>) -> Biome {
     let biome_noise = Perlin::new(100);  // Seed for biome noise
     let scale = 0.05;  // Control size of biome regions
@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+use crate::graphics::cube::Vertex;
+
+use super::chunk::ChunkData;
+use super::coords::ChunkPos;
+use super::light::ChunkLight;
+use super::voxels::ChunkVoxels;
+
+// What is kept of an unloaded chunk, the mesh is only kept if the cache was asked to.
+// The light and mesh are dropped when the light next to the chunk changes while it's unloaded.
+pub struct CachedChunk {
+    pub voxels: ChunkVoxels,
+    pub light: Option<ChunkLight>,
+    pub chunk_data: Option<ChunkData>,
+}
+
+impl CachedChunk {
+    pub fn memory_size(&self) -> usize {
+        let mesh_size = self.chunk_data.as_ref().map_or(0, |chunk_data| {
+            chunk_data.vertices.len() * size_of::<Vertex>() + chunk_data.indices.len() * size_of::<u32>()
+        });
+        let light_size = self.light.as_ref().map_or(0, ChunkLight::memory_size);
+        self.voxels.memory_size() + light_size + mesh_size
+    }
+}
+
+// Recently unloaded chunks, so turning back around doesn't generate them all over again.
+// Once the cache holds more than `capacity` bytes the least recently unloaded chunks are dropped.
+pub struct ChunkCache {
+    capacity: usize,
+    keep_meshes: bool,
+    used: usize,
+    entries: HashMap<ChunkPos, (CachedChunk, u64)>,
+    // Entries by the time they were inserted, oldest first
+    recency: BTreeMap<u64, ChunkPos>,
+    clock: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl ChunkCache {
+    pub fn new(capacity: usize, keep_meshes: bool) -> Self {
+        ChunkCache {
+            capacity,
+            keep_meshes,
+            used: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn keeps_meshes(&self) -> bool {
+        self.keep_meshes
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Bytes of chunk data currently held
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn contains(&self, position: ChunkPos) -> bool {
+        self.entries.contains_key(&position)
+    }
+
+    // Keep an unloaded chunk, evicting the least recently used ones to make room
+    pub fn insert(&mut self, position: ChunkPos, voxels: ChunkVoxels, light: ChunkLight, chunk_data: ChunkData) {
+        self.remove(position);
+
+        let chunk = CachedChunk {
+            voxels,
+            light: Some(light),
+            chunk_data: self.keep_meshes.then_some(chunk_data),
+        };
+        let size = chunk.memory_size();
+        if size > self.capacity {
+            return;
+        }
+
+        while self.used + size > self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some((evicted, _)) = self.entries.remove(&oldest) {
+                self.used -= evicted.memory_size();
+            }
+        }
+
+        self.clock += 1;
+        self.used += size;
+        self.recency.insert(self.clock, position);
+        self.entries.insert(position, (chunk, self.clock));
+    }
+
+    // Take a chunk back out of the cache to load it again, counting the lookup as a hit or a miss
+    pub fn take(&mut self, position: ChunkPos) -> Option<CachedChunk> {
+        let chunk = self.remove(position);
+        if chunk.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        chunk
+    }
+
+    // Light that spread into a cached chunk from next door may be gone, so its light and mesh are worked out again
+    pub fn forget_light(&mut self, position: ChunkPos) {
+        let Some((chunk, _)) = self.entries.get_mut(&position) else {
+            return;
+        };
+        self.used -= chunk.memory_size();
+        chunk.light = None;
+        chunk.chunk_data = None;
+        self.used += chunk.memory_size();
+    }
+
+    fn remove(&mut self, position: ChunkPos) -> Option<CachedChunk> {
+        let (chunk, inserted) = self.entries.remove(&position)?;
+        self.recency.remove(&inserted);
+        self.used -= chunk.memory_size();
+        Some(chunk)
+    }
+}
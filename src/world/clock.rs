@@ -0,0 +1,55 @@
+// Time in the world, counted in days. A day starts at midnight, the sun rises at a quarter of the
+// way through it, is highest at half way and sets at three quarters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldClock {
+    // Days since the world was created, including the current one
+    time: f64,
+    // Real seconds a whole day takes
+    pub day_length: f32,
+    // A frozen clock keeps the time of day it was stopped at
+    pub frozen: bool,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        WorldClock::new(WorldClock::MORNING, 20.0 * 60.0)
+    }
+}
+
+impl WorldClock {
+    pub const MIDNIGHT: f32 = 0.0;
+    pub const SUNRISE: f32 = 0.25;
+    pub const MORNING: f32 = 0.3;
+    pub const NOON: f32 = 0.5;
+    pub const SUNSET: f32 = 0.75;
+
+    pub fn new(time_of_day: f32, day_length: f32) -> Self {
+        WorldClock {
+            time: time_of_day.rem_euclid(1.0) as f64,
+            day_length,
+            frozen: false,
+        }
+    }
+
+    // Move the clock on by some real seconds, unless it's frozen
+    pub fn advance(&mut self, seconds: f32) {
+        if !self.frozen && self.day_length > 0.0 {
+            self.time += (seconds / self.day_length) as f64;
+        }
+    }
+
+    // Days that have gone by completely
+    pub fn day(&self) -> u64 {
+        self.time.floor() as u64
+    }
+
+    // How far through the current day it is, from 0 up to 1
+    pub fn time_of_day(&self) -> f32 {
+        self.time.fract() as f32
+    }
+
+    // Jump to a time of the current day, going back in time if it's earlier
+    pub fn set_time_of_day(&mut self, time_of_day: f32) {
+        self.time = self.time.floor() + time_of_day.rem_euclid(1.0) as f64;
+    }
+}
@@ -0,0 +1,158 @@
+use cgmath::Point3;
+
+use crate::constants::world::CHUNK_SIZE;
+
+use super::mesher::FACE_DIRECTIONS;
+use super::voxels::CHUNK_HEIGHT;
+
+// Division rounding towards negative infinity, so -1 / 16 is -1 and not 0 like `/` gives
+pub fn floor_div(value: i32, divisor: i32) -> i32 {
+    value.div_euclid(divisor)
+}
+
+// Remainder that is never negative, the partner of `floor_div`
+pub fn floor_mod(value: i32, divisor: i32) -> i32 {
+    value.rem_euclid(divisor)
+}
+
+// Position of a block in the world. Blocks are centred on whole coordinates,
+// the block at (0, 0, 0) covers -0.5..0.5 on every axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+// Position of a chunk, counted in chunks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+// Position of a block inside its chunk, from 0 up to the chunk's size on every axis
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LocalPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        BlockPos { x, y, z }
+    }
+
+    // Block a point in the world lies in
+    pub fn containing(position: Point3<f64>) -> Self {
+        BlockPos::new(
+            (position.x + 0.5).floor() as i32,
+            (position.y + 0.5).floor() as i32,
+            (position.z + 0.5).floor() as i32,
+        )
+    }
+
+    pub fn center(self) -> Point3<f64> {
+        Point3::new(self.x as f64, self.y as f64, self.z as f64)
+    }
+
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos::new(floor_div(self.x, CHUNK_SIZE), floor_div(self.y, CHUNK_HEIGHT), floor_div(self.z, CHUNK_SIZE))
+    }
+
+    pub fn local(self) -> LocalPos {
+        LocalPos::new(floor_mod(self.x, CHUNK_SIZE), floor_mod(self.y, CHUNK_HEIGHT), floor_mod(self.z, CHUNK_SIZE))
+    }
+
+    // The chunk holding the block and where in that chunk it is
+    pub fn split(self) -> (ChunkPos, LocalPos) {
+        (self.chunk(), self.local())
+    }
+
+    pub fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        BlockPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    // The six blocks sharing a face with this one
+    pub fn neighbours(self) -> [BlockPos; 6] {
+        FACE_DIRECTIONS.map(|(dx, dy, dz)| self.offset(dx, dy, dz))
+    }
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        ChunkPos { x, y, z }
+    }
+
+    // Chunk a point in the world lies in
+    pub fn containing(position: Point3<f64>) -> Self {
+        BlockPos::containing(position).chunk()
+    }
+
+    // Chunk column a point lies over, chunks are full height so the layer is always y = 0,
+    // even for a camera below or above the world
+    pub fn column_containing(position: Point3<f64>) -> Self {
+        ChunkPos { y: 0, ..ChunkPos::containing(position) }
+    }
+
+    // The chunk's block with the lowest coordinates
+    pub fn origin(self) -> BlockPos {
+        BlockPos::new(self.x * CHUNK_SIZE, self.y * CHUNK_HEIGHT, self.z * CHUNK_SIZE)
+    }
+
+    // Where the chunk's mesh space starts in the world, the centre of its origin block
+    pub fn world_origin(self) -> Point3<f64> {
+        self.origin().center()
+    }
+
+    // World position of a block of this chunk
+    pub fn block(self, local: LocalPos) -> BlockPos {
+        self.origin().offset(local.x, local.y, local.z)
+    }
+
+    pub fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        ChunkPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    // The six chunks sharing a face with this one
+    pub fn neighbours(self) -> [ChunkPos; 6] {
+        FACE_DIRECTIONS.map(|(dx, dy, dz)| self.offset(dx, dy, dz))
+    }
+}
+
+impl LocalPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        LocalPos { x, y, z }
+    }
+
+    pub fn in_bounds(self) -> bool {
+        (0..CHUNK_SIZE).contains(&self.x) && (0..CHUNK_HEIGHT).contains(&self.y) && (0..CHUNK_SIZE).contains(&self.z)
+    }
+}
+
+// The terrain generators address chunks and blocks with plain points
+impl From<ChunkPos> for Point3<i32> {
+    fn from(position: ChunkPos) -> Self {
+        Point3::new(position.x, position.y, position.z)
+    }
+}
+
+impl From<Point3<i32>> for ChunkPos {
+    fn from(position: Point3<i32>) -> Self {
+        ChunkPos::new(position.x, position.y, position.z)
+    }
+}
+
+impl From<BlockPos> for Point3<i32> {
+    fn from(position: BlockPos) -> Self {
+        Point3::new(position.x, position.y, position.z)
+    }
+}
+
+impl From<Point3<i32>> for BlockPos {
+    fn from(position: Point3<i32>) -> Self {
+        BlockPos::new(position.x, position.y, position.z)
+    }
+}
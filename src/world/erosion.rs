@@ -0,0 +1,344 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::region_cache::RegionCache;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErosionKind {
+    // Moves material down slopes steeper than `talus` until they settle
+    Thermal {
+        iterations: u32,
+        talus: f32,
+        strength: f32,
+    },
+    // Simulates rain droplets that carve valleys and deposit sediment downhill
+    Hydraulic {
+        droplets_per_column: f32,
+        max_lifetime: u32,
+        inertia: f32,
+        capacity: f32,
+        erode_speed: f32,
+        deposit_speed: f32,
+        evaporate_speed: f32,
+        gravity: f32,
+    },
+}
+
+impl ErosionKind {
+    pub fn thermal() -> Self {
+        ErosionKind::Thermal {
+            iterations: 30,
+            talus: 1.2,
+            strength: 0.5,
+        }
+    }
+
+    pub fn hydraulic() -> Self {
+        ErosionKind::Hydraulic {
+            droplets_per_column: 1.0,
+            max_lifetime: 40,
+            inertia: 0.05,
+            capacity: 4.0,
+            erode_speed: 0.3,
+            deposit_speed: 0.3,
+            evaporate_speed: 0.02,
+            gravity: 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErosionSettings {
+    pub kind: ErosionKind,
+    pub seed: u64,
+    // Columns per side of an eroded region
+    pub region_size: i32,
+    // Extra columns eroded around a region so water can flow in from the neighbours
+    pub margin: i32,
+    // Columns over which neighbouring regions cross-fade at their border, at most twice the margin
+    pub blend: i32,
+    // Eroded regions kept around, the least recently sampled ones are dropped past this
+    pub max_cached_regions: usize,
+}
+
+impl Default for ErosionSettings {
+    fn default() -> Self {
+        ErosionSettings {
+            kind: ErosionKind::hydraulic(),
+            seed: 142,
+            region_size: 64,
+            margin: 16,
+            blend: 8,
+            max_cached_regions: 256,
+        }
+    }
+}
+
+// Erodes fixed-size regions of a base heightfield and keeps the results around,
+// so the cost is paid once per region no matter how many chunks sample it.
+// A cache assumes it is always sampled with the same base height function.
+pub struct ErosionRegions {
+    settings: ErosionSettings,
+    regions: RegionCache<Vec<f32>>,
+}
+
+impl ErosionRegions {
+    pub fn new(settings: ErosionSettings) -> Self {
+        ErosionRegions {
+            settings,
+            regions: RegionCache::new(settings.max_cached_regions),
+        }
+    }
+
+    pub fn settings(&self) -> &ErosionSettings {
+        &self.settings
+    }
+
+    // Eroded height of a world column. Near a region border the eroded margins of the regions
+    // on either side overlap, and the column is cross-faded between them.
+    pub fn height(&self, base: &dyn Fn(i32, i32) -> f64, world_x: i32, world_z: i32) -> f64 {
+        let region_size = self.settings.region_size;
+        let margin = self.settings.margin;
+        let size = region_size + 2 * margin;
+
+        let mut height = 0.0;
+        for (region_x, weight_x) in self.axis_weights(world_x) {
+            for (region_z, weight_z) in self.axis_weights(world_z) {
+                let weight = weight_x * weight_z;
+                if weight <= 0.0 {
+                    continue;
+                }
+
+                let heights = self
+                    .regions
+                    .get_or_insert_with((region_x, region_z), || erode_region(base, (region_x, region_z), &self.settings));
+                let x = world_x - region_x * region_size + margin;
+                let z = world_z - region_z * region_size + margin;
+                height += heights[(x * size + z) as usize] * weight;
+            }
+        }
+        height as f64
+    }
+
+    pub fn cached_regions(&self) -> usize {
+        self.regions.len()
+    }
+
+    // The regions a world coordinate is blended from along one axis and how much each counts.
+    // Away from the borders that is only the region it lies in, right on a border both sides count half.
+    fn axis_weights(&self, world: i32) -> [(i32, f32); 2] {
+        let region_size = self.settings.region_size;
+        let blend = self.settings.blend.min(2 * self.settings.margin).min(region_size);
+        let region = world.div_euclid(region_size);
+        if blend <= 0 {
+            return [(region, 1.0), (region, 0.0)];
+        }
+
+        // Distance from the middle of the column to the nearest border of its region
+        let middle = world.rem_euclid(region_size) as f32 + 0.5;
+        let (distance, neighbour) = if middle < region_size as f32 / 2.0 {
+            (middle, region - 1)
+        } else {
+            (region_size as f32 - middle, region + 1)
+        };
+        let own = smoothstep((0.5 + distance / blend as f32).min(1.0));
+        [(region, own), (neighbour, 1.0 - own)]
+    }
+}
+
+// Erode one region together with the margin around it, returns `region_size + 2 * margin` columns per side
+pub fn erode_region(base: &dyn Fn(i32, i32) -> f64, region: (i32, i32), settings: &ErosionSettings) -> Vec<f32> {
+    let region_size = settings.region_size;
+    let margin = settings.margin;
+    let size = (region_size + 2 * margin) as usize;
+    let origin_x = region.0 * region_size - margin;
+    let origin_z = region.1 * region_size - margin;
+
+    let mut heights = vec![0.0; size * size];
+    for x in 0..size {
+        for z in 0..size {
+            heights[x * size + z] = base(origin_x + x as i32, origin_z + z as i32) as f32;
+        }
+    }
+
+    match settings.kind {
+        ErosionKind::Thermal { iterations, talus, strength } => {
+            thermal_erosion(&mut heights, size, iterations, talus, strength);
+        }
+        ErosionKind::Hydraulic { .. } => {
+            // Every region gets its own droplets, derived from the seed and its coordinates
+            let region_seed = settings.seed
+                ^ (region.0 as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (region.1 as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+            hydraulic_erosion(&mut heights, size, &settings.kind, region_seed);
+        }
+    }
+
+    heights
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Move material from each column to lower neighbours whenever the drop is steeper than `talus`
+pub fn thermal_erosion(heights: &mut [f32], size: usize, iterations: u32, talus: f32, strength: f32) {
+    let mut deltas = vec![0.0; heights.len()];
+    let neighbours: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+    for _ in 0..iterations {
+        deltas.iter_mut().for_each(|delta| *delta = 0.0);
+
+        for x in 0..size {
+            for z in 0..size {
+                let index = x * size + z;
+                let height = heights[index];
+
+                // Find how much steeper than talus each lower neighbour is
+                let mut total_excess = 0.0;
+                let mut max_excess: f32 = 0.0;
+                let mut excesses = [0.0; 4];
+                for (i, (dx, dz)) in neighbours.iter().enumerate() {
+                    let nx = x as i32 + dx;
+                    let nz = z as i32 + dz;
+                    if nx < 0 || nz < 0 || nx >= size as i32 || nz >= size as i32 {
+                        continue;
+                    }
+                    let excess = height - heights[nx as usize * size + nz as usize] - talus;
+                    if excess > 0.0 {
+                        excesses[i] = excess;
+                        total_excess += excess;
+                        max_excess = max_excess.max(excess);
+                    }
+                }
+
+                if total_excess <= 0.0 {
+                    continue;
+                }
+
+                // Spread the moved material proportionally to how steep each slope is
+                let moved = strength * max_excess * 0.5;
+                deltas[index] -= moved;
+                for (i, (dx, dz)) in neighbours.iter().enumerate() {
+                    if excesses[i] > 0.0 {
+                        let neighbour = (x as i32 + dx) as usize * size + (z as i32 + dz) as usize;
+                        deltas[neighbour] += moved * excesses[i] / total_excess;
+                    }
+                }
+            }
+        }
+
+        for (height, delta) in heights.iter_mut().zip(deltas.iter()) {
+            *height += delta;
+        }
+    }
+}
+
+// Droplet based hydraulic erosion, each droplet rolls downhill picking up and dropping sediment
+pub fn hydraulic_erosion(heights: &mut [f32], size: usize, kind: &ErosionKind, seed: u64) {
+    let ErosionKind::Hydraulic {
+        droplets_per_column,
+        max_lifetime,
+        inertia,
+        capacity,
+        erode_speed,
+        deposit_speed,
+        evaporate_speed,
+        gravity,
+    } = *kind
+    else {
+        return;
+    };
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let droplets = (droplets_per_column * (size * size) as f32) as u32;
+    let max_position = (size - 1) as f32;
+
+    for _ in 0..droplets {
+        let mut x: f32 = rng.gen_range(0.0..max_position);
+        let mut z: f32 = rng.gen_range(0.0..max_position);
+        let mut direction_x = 0.0;
+        let mut direction_z = 0.0;
+        let mut speed = 1.0;
+        let mut water = 1.0;
+        let mut sediment = 0.0;
+
+        for _ in 0..max_lifetime {
+            let (height, gradient_x, gradient_z) = height_and_gradient(heights, size, x, z);
+
+            // Blend the previous direction with the downhill direction
+            direction_x = direction_x * inertia - gradient_x * (1.0 - inertia);
+            direction_z = direction_z * inertia - gradient_z * (1.0 - inertia);
+            let length = (direction_x * direction_x + direction_z * direction_z).sqrt();
+            if length < f32::EPSILON {
+                break;
+            }
+            direction_x /= length;
+            direction_z /= length;
+
+            let old_x = x;
+            let old_z = z;
+            x += direction_x;
+            z += direction_z;
+            if x < 0.0 || z < 0.0 || x >= max_position || z >= max_position {
+                break;
+            }
+
+            let (new_height, _, _) = height_and_gradient(heights, size, x, z);
+            let height_difference = new_height - height;
+
+            let sediment_capacity = (-height_difference).max(0.01) * speed * water * capacity;
+            if sediment > sediment_capacity || height_difference > 0.0 {
+                // Going uphill fills the pit behind us, otherwise drop what we can't carry
+                let deposit = if height_difference > 0.0 {
+                    height_difference.min(sediment)
+                } else {
+                    (sediment - sediment_capacity) * deposit_speed
+                };
+                sediment -= deposit;
+                apply_bilinear(heights, size, old_x, old_z, deposit);
+            } else {
+                // Never dig deeper than the height difference, that would create spikes
+                let erode = ((sediment_capacity - sediment) * erode_speed).min(-height_difference);
+                sediment += erode;
+                apply_bilinear(heights, size, old_x, old_z, -erode);
+            }
+
+            speed = (speed * speed + height_difference.abs() * gravity).sqrt();
+            water *= 1.0 - evaporate_speed;
+        }
+    }
+}
+
+fn height_and_gradient(heights: &[f32], size: usize, x: f32, z: f32) -> (f32, f32, f32) {
+    let cell_x = x.floor() as usize;
+    let cell_z = z.floor() as usize;
+    let offset_x = x - cell_x as f32;
+    let offset_z = z - cell_z as f32;
+
+    let h00 = heights[cell_x * size + cell_z];
+    let h10 = heights[(cell_x + 1) * size + cell_z];
+    let h01 = heights[cell_x * size + cell_z + 1];
+    let h11 = heights[(cell_x + 1) * size + cell_z + 1];
+
+    let gradient_x = (h10 - h00) * (1.0 - offset_z) + (h11 - h01) * offset_z;
+    let gradient_z = (h01 - h00) * (1.0 - offset_x) + (h11 - h10) * offset_x;
+    let height = h00 * (1.0 - offset_x) * (1.0 - offset_z)
+        + h10 * offset_x * (1.0 - offset_z)
+        + h01 * (1.0 - offset_x) * offset_z
+        + h11 * offset_x * offset_z;
+
+    (height, gradient_x, gradient_z)
+}
+
+fn apply_bilinear(heights: &mut [f32], size: usize, x: f32, z: f32, amount: f32) {
+    let cell_x = x.floor() as usize;
+    let cell_z = z.floor() as usize;
+    let offset_x = x - cell_x as f32;
+    let offset_z = z - cell_z as f32;
+
+    heights[cell_x * size + cell_z] += amount * (1.0 - offset_x) * (1.0 - offset_z);
+    heights[(cell_x + 1) * size + cell_z] += amount * offset_x * (1.0 - offset_z);
+    heights[cell_x * size + cell_z + 1] += amount * (1.0 - offset_x) * offset_z;
+    heights[(cell_x + 1) * size + cell_z + 1] += amount * offset_x * offset_z;
+}
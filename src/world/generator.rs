@@ -0,0 +1,242 @@
+use std::sync::{Arc, OnceLock};
+
+use cgmath::Point3;
+
+use super::block::Block;
+use super::erosion::ErosionRegions;
+use super::heightfield::HeightField;
+use super::noise_layer::{NoiseLayer, NoiseSettings};
+use super::road::{build_roads, RoadNetwork};
+use super::structure::{structures_intersecting, StructureSettings};
+use super::terrain::{
+    arch_height, generate_arch_mountain_terrain, generate_flat_terrain, generate_floating_islands,
+    generate_mountainous_terrain, generate_spiral_mountain_terrain, generate_terrain_chunk, generate_trees, hills_height,
+    mountain_height, spiral_height,
+};
+use super::voxels::ChunkVoxels;
+
+// One terrain generator and the parameters it is run with
+#[derive(Clone)]
+pub enum GeneratorPass {
+    Flat {
+        height: i32,
+        block: Block,
+    },
+    Hills {
+        noise: NoiseSettings,
+        height_scale: f64,
+        base_height: i32,
+        block: Block,
+    },
+    Mountains {
+        noise: NoiseSettings,
+        // Shared between chunks so every region is only eroded once
+        erosion: Option<Arc<ErosionRegions>>,
+        height_scale: f64,
+        upper_block: Block,
+        lower_block: Block,
+    },
+    Trees {
+        noise: NoiseSettings,
+        density: f64,
+        height: i32,
+        trunk_block: Block,
+        leaf_block: Block,
+    },
+    Spiral {
+        mountain_width: i32,
+        scale: f64,
+        height_scale: f64,
+        blocks: (Block, Block),
+        spiral_factor: f64,
+        num_rings: f64,
+    },
+    Arches {
+        mountain_width: i32,
+        height_scale: f64,
+        blocks: (Block, Block),
+        arch_factor: f64,
+        num_arches: i32,
+    },
+    FloatingIslands {
+        coverage: NoiseSettings,
+        shape: NoiseSettings,
+        altitude: i32,
+        depth: i32,
+        surface_block: Block,
+        underside_block: Block,
+        waterfall_block: Block,
+    },
+    // Shared between chunks so every region is only planned once
+    Roads(Arc<RoadNetwork>),
+    // Placed on top of the ground the passes before it built
+    Structures(StructureSettings),
+}
+
+impl GeneratorPass {
+    // Noise layers the pass samples, built from its settings in the order they're listed
+    fn noise_layers(&self) -> Vec<NoiseLayer> {
+        match self {
+            GeneratorPass::Hills { noise, .. } | GeneratorPass::Mountains { noise, .. } | GeneratorPass::Trees { noise, .. } => {
+                vec![NoiseLayer::new(noise)]
+            }
+            GeneratorPass::FloatingIslands { coverage, shape, .. } => vec![NoiseLayer::new(coverage), NoiseLayer::new(shape)],
+            _ => Vec::new(),
+        }
+    }
+
+    // `noise` holds the layers `noise_layers` built for this pass
+    pub fn generate(&self, chunk_position: Point3<i32>, config: &GeneratorConfig, noise: &[NoiseLayer], voxels: &mut ChunkVoxels) {
+        let flat_height = config.flat_height;
+
+        match self {
+            GeneratorPass::Flat { height, block } => {
+                generate_flat_terrain(*height, voxels, *block);
+            }
+            GeneratorPass::Hills { height_scale, base_height, block, .. } => {
+                generate_terrain_chunk(chunk_position, voxels, &noise[0], *height_scale, *base_height, *block);
+            }
+            GeneratorPass::Mountains { erosion, height_scale, upper_block, lower_block, .. } => {
+                generate_mountainous_terrain(
+                    chunk_position,
+                    flat_height,
+                    voxels,
+                    &noise[0],
+                    erosion.as_deref(),
+                    *height_scale,
+                    *upper_block,
+                    *lower_block,
+                    config.seed as u64,
+                );
+            }
+            GeneratorPass::Trees { density, height, trunk_block, leaf_block, .. } => {
+                generate_trees(chunk_position, flat_height, voxels, &noise[0], *density, *height, *trunk_block, *leaf_block);
+            }
+            GeneratorPass::Spiral { mountain_width, scale, height_scale, blocks, spiral_factor, num_rings } => {
+                generate_spiral_mountain_terrain(
+                    chunk_position,
+                    flat_height,
+                    voxels,
+                    *mountain_width,
+                    *scale,
+                    *height_scale,
+                    blocks.0,
+                    blocks.1,
+                    *spiral_factor,
+                    *num_rings,
+                );
+            }
+            GeneratorPass::Arches { mountain_width, height_scale, blocks, arch_factor, num_arches } => {
+                generate_arch_mountain_terrain(
+                    chunk_position,
+                    flat_height,
+                    voxels,
+                    *mountain_width,
+                    *height_scale,
+                    blocks.0,
+                    blocks.1,
+                    *arch_factor,
+                    *num_arches,
+                );
+            }
+            GeneratorPass::FloatingIslands {
+                altitude,
+                depth,
+                surface_block,
+                underside_block,
+                waterfall_block,
+                ..
+            } => {
+                generate_floating_islands(
+                    chunk_position,
+                    flat_height,
+                    voxels,
+                    &noise[0],
+                    &noise[1],
+                    *altitude,
+                    *depth,
+                    *surface_block,
+                    *underside_block,
+                    *waterfall_block,
+                );
+            }
+            GeneratorPass::Roads(network) => {
+                let surface = config.surface_height_field();
+                let roads = network.roads_intersecting(chunk_position, &surface);
+                build_roads(chunk_position, &roads, network.settings(), &surface, voxels);
+            }
+            GeneratorPass::Structures(settings) => {
+                let surface = config.surface_height_field();
+                for piece in structures_intersecting(settings, chunk_position, &surface) {
+                    piece.place(chunk_position, voxels);
+                }
+            }
+        }
+    }
+
+    // Highest ground block this pass puts in a world column, if it builds ground at all
+    fn ground_height(&self, noise: Option<&NoiseLayer>, flat_height: i32, world_x: i32, world_z: i32) -> Option<i32> {
+        match (self, noise) {
+            (GeneratorPass::Flat { height, .. }, _) => Some(*height),
+            (GeneratorPass::Hills { height_scale, base_height, .. }, Some(noise)) => {
+                Some(hills_height(noise, *height_scale, *base_height, world_x, world_z) - 1)
+            }
+            (GeneratorPass::Mountains { erosion, height_scale, .. }, Some(noise)) => {
+                Some(mountain_height(noise, erosion.as_deref(), *height_scale, flat_height, world_x, world_z))
+            }
+            (GeneratorPass::Spiral { mountain_width, scale, height_scale, spiral_factor, num_rings, .. }, _) => {
+                let height = spiral_height(flat_height, *scale, *height_scale, *spiral_factor, *num_rings, world_x, world_z);
+                Some(height + mountain_width - 1)
+            }
+            (GeneratorPass::Arches { mountain_width, height_scale, arch_factor, num_arches, .. }, _) => {
+                Some(arch_height(flat_height, *height_scale, *arch_factor, *num_arches, world_x) + mountain_width - 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Everything needed to generate the chunks of a world, passes run in order
+pub struct GeneratorConfig {
+    pub seed: u32,
+    pub flat_height: i32,
+    pub passes: Vec<GeneratorPass>,
+    // Noise layers of every pass, built once the first chunk needs them and shared from then on
+    noise: OnceLock<Vec<Vec<NoiseLayer>>>,
+}
+
+// A copy builds its own noise layers, so its passes can still be changed before it's used
+impl Clone for GeneratorConfig {
+    fn clone(&self) -> Self {
+        GeneratorConfig::new(self.seed, self.flat_height, self.passes.clone())
+    }
+}
+
+impl GeneratorConfig {
+    pub fn new(seed: u32, flat_height: i32, passes: Vec<GeneratorPass>) -> Self {
+        GeneratorConfig { seed, flat_height, passes, noise: OnceLock::new() }
+    }
+
+    // Noise layers of every pass, in the same order as the passes
+    pub fn noise_layers(&self) -> &[Vec<NoiseLayer>] {
+        let layers = self.noise.get_or_init(|| self.passes.iter().map(GeneratorPass::noise_layers).collect());
+        assert_eq!(layers.len(), self.passes.len(), "passes were changed after their noise layers were built");
+        layers
+    }
+
+    // Height of the highest ground block of every world column, trees and islands aren't ground.
+    // Sampled straight from the passes so no chunk has to be generated to know it.
+    pub fn surface_height_field(&self) -> HeightField<'_> {
+        let layers = self.noise_layers();
+        let flat_height = self.flat_height;
+
+        HeightField::new(move |world_x, world_z| {
+            self.passes
+                .iter()
+                .zip(layers)
+                .filter_map(|(pass, noise)| pass.ground_height(noise.first(), flat_height, world_x, world_z))
+                .max()
+                .unwrap_or(flat_height)
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use cgmath::Point3;
+
+use crate::constants::world::CHUNK_SIZE;
+
+// Terrain height for every world column, shared by all the generators.
+// Heights are always looked up by world column, so two chunks asking for the same column get the same answer.
+pub struct HeightField<'a> {
+    height: Box<dyn Fn(i32, i32) -> i32 + 'a>,
+}
+
+impl<'a> HeightField<'a> {
+    pub fn new(height: impl Fn(i32, i32) -> i32 + 'a) -> Self {
+        HeightField {
+            height: Box::new(height),
+        }
+    }
+
+    // Height of the column at a world position
+    pub fn sample(&self, world_x: i32, world_z: i32) -> i32 {
+        (self.height)(world_x, world_z)
+    }
+}
+
+// World column of a chunk local (x, z), local coordinates may reach into the neighbours
+pub fn column_world_position(chunk_position: Point3<i32>, x: i32, z: i32) -> (i32, i32) {
+    (chunk_position.x * CHUNK_SIZE + x, chunk_position.z * CHUNK_SIZE + z)
+}
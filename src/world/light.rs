@@ -0,0 +1,366 @@
+use std::collections::VecDeque;
+
+use crate::constants::world::CHUNK_SIZE;
+
+use super::block::Block;
+use super::border::ChunkBorder;
+use super::coords::{BlockPos, ChunkPos};
+use super::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+// Brightest a light level gets, sunlight under the open sky and the brightest light giving blocks
+pub const MAX_LIGHT: u8 = 15;
+
+// Number of the face pointing down in `FACE_DIRECTIONS`
+const DOWN: usize = 3;
+
+// Light levels of a block, from the sky and from light giving blocks, each from 0 up to `MAX_LIGHT`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Light {
+    pub sun: u8,
+    pub block: u8,
+}
+
+impl Light {
+    // Under the open sky, with no light giving blocks around
+    pub const SKY: Light = Light { sun: MAX_LIGHT, block: 0 };
+
+    pub fn new(sun: u8, block: u8) -> Self {
+        Light { sun, block }
+    }
+}
+
+// Both kinds of light spread the same way, except sunlight keeps its full strength going straight down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Channel {
+    Sun,
+    Block,
+}
+
+impl Channel {
+    fn get(self, light: Light) -> u8 {
+        match self {
+            Channel::Sun => light.sun,
+            Channel::Block => light.block,
+        }
+    }
+
+    fn with(self, light: Light, level: u8) -> Light {
+        match self {
+            Channel::Sun => Light { sun: level, ..light },
+            Channel::Block => Light { block: level, ..light },
+        }
+    }
+}
+
+// Light of every block of a chunk column, laid out like `ChunkVoxels`
+#[derive(Clone)]
+pub struct ChunkLight {
+    // Sunlight in the high 4 bits, block light in the low 4
+    levels: Vec<u8>,
+}
+
+impl Default for ChunkLight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkLight {
+    // Completely dark
+    pub fn new() -> Self {
+        ChunkLight {
+            levels: vec![0; (CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE) as usize],
+        }
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        ((x * CHUNK_SIZE + z) * CHUNK_HEIGHT + y) as usize
+    }
+
+    // Outside the chunk, where its light isn't known, it reads as open sky
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Light {
+        if !ChunkVoxels::in_bounds(x, y, z) {
+            return Light::SKY;
+        }
+        let level = self.levels[Self::index(x, y, z)];
+        Light::new(level >> 4, level & 0xf)
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, light: Light) {
+        if ChunkVoxels::in_bounds(x, y, z) {
+            self.levels[Self::index(x, y, z)] = (light.sun.min(MAX_LIGHT) << 4) | light.block.min(MAX_LIGHT);
+        }
+    }
+
+    // Bytes the light levels take up
+    pub fn memory_size(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Light of a chunk on its own, sunlight from the sky above it and block light from its own light
+    // giving blocks. Light from the chunks around it is added by `spread_between_chunks` once it's loaded.
+    pub fn compute(voxels: &ChunkVoxels) -> Self {
+        let mut light = ChunkLight::new();
+        let mut lit = LoneChunk { voxels, light: &mut light };
+
+        // Sunlight falls down every column from above the top of the world until something stops it.
+        // `floors` is the lowest block of each column the sky lights fully, everything from there up is open sky.
+        let mut floors = [[CHUNK_HEIGHT; CHUNK_SIZE as usize]; CHUNK_SIZE as usize];
+        let mut sunlit = VecDeque::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let mut level = MAX_LIGHT;
+                for y in (0..CHUNK_HEIGHT).rev() {
+                    level = spread(Channel::Sun, level, true, voxels.get(x, y, z));
+                    if level == 0 {
+                        break;
+                    }
+                    lit.light.set(x, y, z, Light::new(level, 0));
+                    if level == MAX_LIGHT {
+                        floors[x as usize][z as usize] = y;
+                    } else {
+                        // Under water, where it's dimmer than the sky
+                        sunlit.push_back(BlockPos::new(x, y, z));
+                    }
+                }
+            }
+        }
+
+        // Then it spreads sideways from the open sky, but only next to columns it doesn't reach as far down in
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let floor = floors[x as usize][z as usize];
+                let lowest_neighbour = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .into_iter()
+                    .filter(|&(dx, dz)| ChunkVoxels::in_bounds(x + dx, 0, z + dz))
+                    .map(|(dx, dz)| floors[(x + dx) as usize][(z + dz) as usize])
+                    .max()
+                    .unwrap_or(floor);
+                sunlit.extend((floor..lowest_neighbour).map(|y| BlockPos::new(x, y, z)));
+            }
+        }
+        spread_light(&mut lit, Channel::Sun, &mut sunlit);
+
+        let mut sources = VecDeque::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_HEIGHT {
+                    let emission = voxels.get(x, y, z).light_emission();
+                    if emission > 0 {
+                        let sun = lit.light.get(x, y, z).sun;
+                        lit.light.set(x, y, z, Light::new(sun, emission));
+                        sources.push_back(BlockPos::new(x, y, z));
+                    }
+                }
+            }
+        }
+        spread_light(&mut lit, Channel::Block, &mut sources);
+
+        light
+    }
+}
+
+// Light of the blocks just outside a chunk, for lighting its border faces
+pub type BorderLight = ChunkBorder<Light>;
+
+impl BorderLight {
+    pub fn gather(world: &impl LightWorld, chunk: ChunkPos) -> Self {
+        Self::copy_from(chunk, |position| world.chunk_light(position), |(_, light), x, y, z| light.get(x, y, z))
+    }
+}
+
+// Blocks and light of the loaded chunks, for spreading light between them
+pub trait LightWorld {
+    // `None` if the chunk isn't loaded
+    fn chunk_light(&self, position: ChunkPos) -> Option<(&ChunkVoxels, &ChunkLight)>;
+    // Only called for blocks of loaded chunks
+    fn set_light(&mut self, position: BlockPos, light: Light);
+
+    fn block(&self, position: BlockPos) -> Option<Block> {
+        let (chunk, local) = position.split();
+        self.chunk_light(chunk).map(|(voxels, _)| voxels.get(local.x, local.y, local.z))
+    }
+
+    fn light(&self, position: BlockPos) -> Option<Light> {
+        let (chunk, local) = position.split();
+        self.chunk_light(chunk).map(|(_, light)| light.get(local.x, local.y, local.z))
+    }
+}
+
+// A single chunk at the origin, with nothing loaded around it
+struct LoneChunk<'a> {
+    voxels: &'a ChunkVoxels,
+    light: &'a mut ChunkLight,
+}
+
+impl LightWorld for LoneChunk<'_> {
+    fn chunk_light(&self, position: ChunkPos) -> Option<(&ChunkVoxels, &ChunkLight)> {
+        (position == ChunkPos::default()).then_some((self.voxels, &*self.light))
+    }
+
+    fn set_light(&mut self, position: BlockPos, light: Light) {
+        self.light.set(position.x, position.y, position.z, light);
+    }
+}
+
+// Update the light around a block that was just changed, taking away the light it blocks or
+// no longer gives off and spreading light into it if it lets light through
+pub fn relight_block(world: &mut impl LightWorld, position: BlockPos) {
+    let Some((block, _)) = cell(world, position) else {
+        return;
+    };
+
+    for channel in [Channel::Sun, Channel::Block] {
+        let Some((_, light)) = cell(world, position) else {
+            return;
+        };
+        let mut relight = VecDeque::new();
+
+        let level = channel.get(light);
+        if level > 0 {
+            world.set_light(position, channel.with(light, 0));
+            remove_light(world, channel, &mut VecDeque::from([(position, level)]), &mut relight);
+        }
+
+        let emission = if channel == Channel::Block { block.light_emission() } else { 0 };
+        if emission > 0 {
+            let light = world.light(position).unwrap_or_default();
+            world.set_light(position, channel.with(light, emission));
+            relight.push_back(position);
+        }
+
+        // Light around the block spreads back into it and whatever went dark behind it
+        relight.extend(position.neighbours());
+        spread_light(world, channel, &mut relight);
+    }
+}
+
+// Spread light both ways between a chunk that was just loaded and the loaded chunks next to it,
+// its own light was worked out without them
+pub fn spread_between_chunks(world: &mut impl LightWorld, chunk: ChunkPos) {
+    let mut sun = VecDeque::new();
+    let mut block = VecDeque::new();
+
+    for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let neighbour = chunk.offset(dx, 0, dz);
+        let (Some((voxels, light)), Some((neighbour_voxels, neighbour_light))) = (world.chunk_light(chunk), world.chunk_light(neighbour))
+        else {
+            continue;
+        };
+
+        // Only blocks brighter than the one across the border from them have light to spread over it
+        for along in 0..CHUNK_SIZE {
+            // Local positions of the blocks on both sides of the border
+            let (x, z) = match (dx, dz) {
+                (1, 0) => (CHUNK_SIZE - 1, along),
+                (-1, 0) => (0, along),
+                (0, 1) => (along, CHUNK_SIZE - 1),
+                _ => (along, 0),
+            };
+            let (other_x, other_z) = (x + dx - dx.signum() * CHUNK_SIZE, z + dz - dz.signum() * CHUNK_SIZE);
+
+            for y in 0..CHUNK_HEIGHT {
+                let (here, there) = (light.get(x, y, z), neighbour_light.get(other_x, y, other_z));
+                let (here_block, there_block) = (voxels.get(x, y, z), neighbour_voxels.get(other_x, y, other_z));
+                let inside = chunk.origin().offset(x, y, z);
+                let outside = inside.offset(dx, 0, dz);
+
+                for (channel, queue) in [(Channel::Sun, &mut sun), (Channel::Block, &mut block)] {
+                    if spread(channel, channel.get(here), false, there_block) > channel.get(there) {
+                        queue.push_back(inside);
+                    }
+                    if spread(channel, channel.get(there), false, here_block) > channel.get(here) {
+                        queue.push_back(outside);
+                    }
+                }
+            }
+        }
+    }
+
+    spread_light(world, Channel::Sun, &mut sun);
+    spread_light(world, Channel::Block, &mut block);
+}
+
+// Block and light at a position. Above the world is all open sky, below it there's nothing.
+fn cell(world: &impl LightWorld, position: BlockPos) -> Option<(Block, Light)> {
+    if position.y >= CHUNK_HEIGHT {
+        return Some((Block::Air, Light::SKY));
+    }
+    if position.y < 0 {
+        return None;
+    }
+    Some((world.block(position)?, world.light(position)?))
+}
+
+// Level light spreading from a block at `level` has once it's in a neighbouring block
+fn spread(channel: Channel, level: u8, down: bool, into: Block) -> u8 {
+    if into.is_opaque() {
+        return 0;
+    }
+    if channel == Channel::Sun && down && level == MAX_LIGHT && into.light_absorption() == 0 {
+        return MAX_LIGHT;
+    }
+    level.saturating_sub(1 + into.light_absorption())
+}
+
+// Spread light out from the queued blocks, brightening every block it reaches that was darker
+fn spread_light(world: &mut impl LightWorld, channel: Channel, queue: &mut VecDeque<BlockPos>) {
+    while let Some(position) = queue.pop_front() {
+        let Some((_, light)) = cell(world, position) else {
+            continue;
+        };
+        let level = channel.get(light);
+        if level <= 1 {
+            continue;
+        }
+
+        for (face, neighbour) in position.neighbours().into_iter().enumerate() {
+            if neighbour.y >= CHUNK_HEIGHT {
+                continue;
+            }
+            let Some((block, neighbour_light)) = cell(world, neighbour) else {
+                continue;
+            };
+
+            let spread = spread(channel, level, face == DOWN, block);
+            if spread > channel.get(neighbour_light) {
+                world.set_light(neighbour, channel.with(neighbour_light, spread));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+// Take away the light that came from the queued blocks, which had the given levels. Blocks around
+// the darkened area lit by something else are queued in `relight` to spread their light back into it.
+fn remove_light(
+    world: &mut impl LightWorld,
+    channel: Channel,
+    removals: &mut VecDeque<(BlockPos, u8)>,
+    relight: &mut VecDeque<BlockPos>,
+) {
+    while let Some((position, level)) = removals.pop_front() {
+        for (face, neighbour) in position.neighbours().into_iter().enumerate() {
+            if neighbour.y >= CHUNK_HEIGHT {
+                continue;
+            }
+            let Some((block, neighbour_light)) = cell(world, neighbour) else {
+                continue;
+            };
+            let neighbour_level = channel.get(neighbour_light);
+            if neighbour_level == 0 {
+                continue;
+            }
+
+            // Darker neighbours could have been lit from here, and so could sunlight falling straight down
+            let lit_from_here = neighbour_level < level || (channel == Channel::Sun && face == DOWN && level == MAX_LIGHT);
+            let gives_off_light = channel == Channel::Block && block.light_emission() >= neighbour_level;
+            if lit_from_here && !gives_off_light {
+                world.set_light(neighbour, channel.with(neighbour_light, 0));
+                removals.push_back((neighbour, neighbour_level));
+            } else {
+                relight.push_back(neighbour);
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+use super::coords::ChunkPos;
+
+// How much longer a chunk straight behind the camera waits than one straight ahead at the same distance
+const BEHIND_PENALTY: f32 = 2.0;
+// The queue is re-sorted once the view turns further than this from when it was last sorted
+const RESORT_ANGLE_COS: f32 = 0.966; // cos(15 degrees)
+
+// Load order of a chunk, lower loads first. Near chunks come before far ones and at the same
+// distance the chunks in front of the camera come before the ones behind it.
+pub fn chunk_priority(position: ChunkPos, camera_chunk: ChunkPos, view_direction: Vector3<f32>) -> f32 {
+    let offset = Vector2::new((position.x - camera_chunk.x) as f32, (position.z - camera_chunk.z) as f32);
+    let distance = offset.magnitude();
+    let view = Vector2::new(view_direction.x, view_direction.z);
+    if distance == 0.0 || view.magnitude2() == 0.0 {
+        return distance;
+    }
+
+    // 0 looking straight at the chunk, 1 with the chunk straight behind
+    let facing_away = (1.0 - offset.dot(view) / (distance * view.magnitude())) / 2.0;
+    distance * (1.0 + BEHIND_PENALTY * facing_away)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct QueuedChunk {
+    priority: f32,
+    position: ChunkPos,
+}
+
+impl Eq for QueuedChunk {}
+
+impl Ord for QueuedChunk {
+    // Reversed so the heap pops the lowest priority first, ties are broken by position to keep the order stable
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| other.position.cmp(&self.position))
+    }
+}
+
+impl PartialOrd for QueuedChunk {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Chunks waiting to be generated, handed out by `chunk_priority` for the current view.
+// Every queued chunk is eventually popped, turning the camera only changes the order.
+pub struct ChunkLoadQueue {
+    heap: BinaryHeap<QueuedChunk>,
+    queued: HashSet<ChunkPos>,
+    camera_chunk: ChunkPos,
+    view_direction: Vector3<f32>,
+}
+
+impl Default for ChunkLoadQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkLoadQueue {
+    pub fn new() -> Self {
+        ChunkLoadQueue {
+            heap: BinaryHeap::new(),
+            queued: HashSet::new(),
+            camera_chunk: ChunkPos::default(),
+            view_direction: Vector3::new(0.0, 0.0, -1.0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, position: ChunkPos) -> bool {
+        self.queued.contains(&position)
+    }
+
+    // Queue a chunk, chunks already in the queue keep their place
+    pub fn push(&mut self, position: ChunkPos) {
+        if self.queued.insert(position) {
+            let priority = chunk_priority(position, self.camera_chunk, self.view_direction);
+            self.heap.push(QueuedChunk { priority, position });
+        }
+    }
+
+    // The most urgent chunk
+    pub fn pop(&mut self) -> Option<ChunkPos> {
+        let QueuedChunk { position, .. } = self.heap.pop()?;
+        self.queued.remove(&position);
+        Some(position)
+    }
+
+    // Drop every queued chunk `keep` says no to, returns how many were dropped
+    pub fn retain(&mut self, mut keep: impl FnMut(ChunkPos) -> bool) -> usize {
+        let before = self.heap.len();
+        self.heap.retain(|queued| keep(queued.position));
+        self.queued = self.heap.iter().map(|queued| queued.position).collect();
+        before - self.heap.len()
+    }
+
+    // Follow the camera, re-sorting the queue once it has changed chunk or turned far enough
+    pub fn update_view(&mut self, camera_chunk: ChunkPos, view_direction: Vector3<f32>) {
+        let turned = view_direction.dot(self.view_direction) < RESORT_ANGLE_COS * view_direction.magnitude() * self.view_direction.magnitude();
+        if camera_chunk == self.camera_chunk && !turned {
+            return;
+        }
+
+        self.camera_chunk = camera_chunk;
+        self.view_direction = view_direction;
+        let positions: Vec<ChunkPos> = self.heap.drain().map(|queued| queued.position).collect();
+        self.heap = positions
+            .into_iter()
+            .map(|position| QueuedChunk { priority: chunk_priority(position, camera_chunk, view_direction), position })
+            .collect();
+    }
+}
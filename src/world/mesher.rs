@@ -0,0 +1,89 @@
+use cgmath::Vector3;
+
+use crate::constants::world::CHUNK_SIZE;
+use crate::graphics::texture::get_uvs;
+use crate::shapes::cube::create_cube_face;
+
+use super::block::Block;
+use super::border::BorderBlocks;
+use super::chunk::ChunkData;
+use super::light::{BorderLight, ChunkLight, Light};
+use super::visibility::ChunkVisibility;
+use super::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+// Direction each cube face points in, same order as the faces of `create_cube`
+pub const FACE_DIRECTIONS: [(i32, i32, i32); 6] = [
+    (0, 0, 1),  // Front
+    (0, 0, -1), // Back
+    (0, 1, 0),  // Top
+    (0, -1, 0), // Bottom
+    (1, 0, 0),  // Right
+    (-1, 0, 0), // Left
+];
+
+// Build the mesh of a chunk lit by its own light, the chunks around it aren't looked at
+pub fn mesh_chunk(voxels: &ChunkVoxels, light: &ChunkLight) -> ChunkData {
+    mesh_chunk_with_light(voxels, &BorderBlocks::default(), |x, y, z| light.get(x, y, z))
+}
+
+// Build the mesh of a chunk lit by its own light and the light just outside its sides,
+// the faces its border blocks share with the blocks of the chunks next to it are culled
+pub fn mesh_chunk_with_borders(voxels: &ChunkVoxels, light: &ChunkLight, blocks: &BorderBlocks, border_light: &BorderLight) -> ChunkData {
+    mesh_chunk_with_light(voxels, blocks, |x, y, z| border_light.get(x, y, z).unwrap_or_else(|| light.get(x, y, z)))
+}
+
+// Build the mesh of a chunk, only emitting the faces that touch a see-through neighbour. Every face
+// takes the light of the block in front of it, which `light` gives in chunk local coordinates and
+// can be one block outside the chunk.
+pub fn mesh_chunk_with_light(voxels: &ChunkVoxels, border: &BorderBlocks, light: impl Fn(i32, i32, i32) -> Light) -> ChunkData {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let Some(surface_height) = voxels.surface_height(x, z) else {
+                continue;
+            };
+
+            for y in 0..=surface_height {
+                let block = voxels.get(x, y, z);
+                if block.is_air() {
+                    continue;
+                }
+                let uvs = get_uvs(block);
+
+                for (face, (dx, dy, dz)) in FACE_DIRECTIONS.iter().enumerate() {
+                    if !is_face_visible(voxels, border, block, x + dx, y + dy, z + dz) {
+                        continue;
+                    }
+
+                    let offset = Vector3::new(x as f32, y as f32, z as f32);
+                    let base_index = vertices.len() as u32;
+                    let face_light = light(x + dx, y + dy, z + dz);
+                    vertices.extend_from_slice(&create_cube_face(offset, face, uvs.face(face), face_light));
+                    indices.extend_from_slice(&[0, 1, 2, 2, 3, 0].map(|idx| idx + base_index));
+                }
+            }
+        }
+    }
+
+    ChunkData {
+        vertices,
+        indices,
+        visibility: ChunkVisibility::compute(voxels),
+    }
+}
+
+fn is_face_visible(voxels: &ChunkVoxels, border: &BorderBlocks, block: Block, x: i32, y: i32, z: i32) -> bool {
+    // Nothing can look at the underside of the world
+    if y < 0 {
+        return false;
+    }
+    if y >= CHUNK_HEIGHT {
+        return true;
+    }
+
+    // Past the sides the loaded neighbours decide, faces towards a chunk that isn't loaded are kept
+    let neighbour = border.get(x, y, z).unwrap_or_else(|| voxels.get(x, y, z));
+    !neighbour.is_opaque() && neighbour != block
+}
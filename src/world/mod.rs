@@ -1,4 +1,25 @@
 pub mod chunk;
+#[allow(clippy::module_inception)]
 pub mod world;
 pub mod terrain;
-pub mod types;
\ No newline at end of file
+pub mod types;
+pub mod noise_layer;
+pub mod heightfield;
+pub mod erosion;
+pub mod generator;
+pub mod preset;
+pub mod block;
+pub mod voxels;
+pub mod mesher;
+pub mod structure;
+pub mod road;
+pub mod workers;
+pub mod load_queue;
+pub mod render_distance;
+pub mod chunk_cache;
+pub mod coords;
+pub mod visibility;
+pub mod clock;
+pub mod light;
+pub mod region_cache;
+pub mod border;
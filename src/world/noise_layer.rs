@@ -0,0 +1,217 @@
+use noise::core::worley::{distance_functions, worley_2d, worley_3d, ReturnType};
+use noise::permutationtable::PermutationTable;
+use noise::{Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, RidgedMulti, Seedable, Vector2, Vector3};
+
+// Base noise function a layer samples from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseKind {
+    Perlin,
+    OpenSimplex,
+    Worley,
+}
+
+// How the octaves of a layer are combined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fractal {
+    // A single octave of the base noise
+    Single,
+    // Fractal brownian motion, good for rolling hills
+    Fbm,
+    // Ridged multifractal, gives sharp mountain ridges
+    Ridged,
+}
+
+// Offsets the sample position by another noise field before sampling
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DomainWarp {
+    pub frequency: f64,
+    pub amplitude: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseSettings {
+    pub kind: NoiseKind,
+    pub fractal: Fractal,
+    pub seed: u32,
+    pub frequency: f64,
+    pub octaves: usize,
+    pub lacunarity: f64,
+    pub persistence: f64,
+    pub warp: Option<DomainWarp>,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        NoiseSettings {
+            kind: NoiseKind::Perlin,
+            fractal: Fractal::Single,
+            seed: 0,
+            frequency: 1.0,
+            octaves: 4,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            warp: None,
+        }
+    }
+}
+
+const MAX_OCTAVES: usize = 32;
+
+trait Source: NoiseFn<f64, 2> + NoiseFn<f64, 3> + Send + Sync {}
+
+impl<T: NoiseFn<f64, 2> + NoiseFn<f64, 3> + Send + Sync> Source for T {}
+
+// A noise stack built from `NoiseSettings`, ready to be sampled.
+// Building one takes a while, so it's built once and shared between the threads sampling it.
+pub struct NoiseLayer {
+    source: Box<dyn Source>,
+    warp: Option<(Box<dyn Source>, DomainWarp)>,
+    frequency: f64,
+}
+
+impl NoiseLayer {
+    pub fn new(settings: &NoiseSettings) -> Self {
+        let source = build_source(settings);
+
+        // The warp field is its own fbm stack so it doesn't line up with the features it displaces
+        let warp = settings.warp.map(|warp| {
+            let warp_settings = NoiseSettings {
+                kind: NoiseKind::Perlin,
+                fractal: Fractal::Fbm,
+                seed: settings.seed.wrapping_add(1000),
+                octaves: 3,
+                ..*settings
+            };
+            (build_source(&warp_settings), warp)
+        });
+
+        NoiseLayer {
+            source,
+            warp,
+            frequency: settings.frequency,
+        }
+    }
+
+    // Sample the layer at a world column
+    pub fn get(&self, x: f64, z: f64) -> f64 {
+        let (mut x, mut z) = (x, z);
+
+        if let Some((warp_source, warp)) = &self.warp {
+            let wx = x * warp.frequency;
+            let wz = z * warp.frequency;
+            // Sample the warp field twice at far apart offsets to get two independent displacements
+            let dx = NoiseFn::<f64, 2>::get(warp_source.as_ref(), [wx, wz]);
+            let dz = NoiseFn::<f64, 2>::get(warp_source.as_ref(), [wx + 31.7, wz + 47.3]);
+            x += dx * warp.amplitude;
+            z += dz * warp.amplitude;
+        }
+
+        NoiseFn::<f64, 2>::get(self.source.as_ref(), [x * self.frequency, z * self.frequency])
+    }
+
+    // Sample the layer at a world position, used for 3d density masks
+    pub fn get3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (mut x, mut y, mut z) = (x, y, z);
+
+        if let Some((warp_source, warp)) = &self.warp {
+            let wx = x * warp.frequency;
+            let wy = y * warp.frequency;
+            let wz = z * warp.frequency;
+            let dx = NoiseFn::<f64, 3>::get(warp_source.as_ref(), [wx, wy, wz]);
+            let dy = NoiseFn::<f64, 3>::get(warp_source.as_ref(), [wx + 31.7, wy + 12.9, wz + 47.3]);
+            let dz = NoiseFn::<f64, 3>::get(warp_source.as_ref(), [wx + 73.1, wy + 5.3, wz + 19.7]);
+            x += dx * warp.amplitude;
+            y += dy * warp.amplitude;
+            z += dz * warp.amplitude;
+        }
+
+        NoiseFn::<f64, 3>::get(
+            self.source.as_ref(),
+            [x * self.frequency, y * self.frequency, z * self.frequency],
+        )
+    }
+}
+
+fn build_source(settings: &NoiseSettings) -> Box<dyn Source> {
+    match settings.kind {
+        NoiseKind::Perlin => build_fractal(settings, Perlin::new),
+        NoiseKind::OpenSimplex => build_fractal(settings, OpenSimplex::new),
+        NoiseKind::Worley => build_fractal(settings, WorleyDistance::new),
+    }
+}
+
+fn build_fractal<T>(settings: &NoiseSettings, base: fn(u32) -> T) -> Box<dyn Source>
+where
+    T: Source + Default + Seedable + 'static,
+{
+    // Each octave gets its own seed so they don't correlate
+    let octaves = settings.octaves.clamp(1, MAX_OCTAVES);
+    let sources = || -> Vec<T> {
+        (0..octaves as u32)
+            .map(|octave| base(settings.seed.wrapping_add(octave)))
+            .collect()
+    };
+
+    // Frequency is applied by the layer itself so every fractal mode scales the same way
+    match settings.fractal {
+        Fractal::Single => Box::new(base(settings.seed)),
+        Fractal::Fbm => Box::new(
+            Fbm::<T>::new(settings.seed)
+                .set_octaves(octaves)
+                .set_frequency(1.0)
+                .set_lacunarity(settings.lacunarity)
+                .set_persistence(settings.persistence)
+                .set_sources(sources()),
+        ),
+        Fractal::Ridged => Box::new(
+            RidgedMulti::<T>::new(settings.seed)
+                .set_octaves(octaves)
+                .set_frequency(1.0)
+                .set_lacunarity(settings.lacunarity)
+                .set_persistence(settings.persistence)
+                .set_sources(sources()),
+        ),
+    }
+}
+
+// Distance to the nearest Worley cell point. The noise crate's `Worley` holds its distance function in an `Rc`,
+// which keeps it from being shared between threads.
+#[derive(Clone, Copy)]
+struct WorleyDistance {
+    seed: u32,
+    permutations: PermutationTable,
+}
+
+impl WorleyDistance {
+    fn new(seed: u32) -> Self {
+        WorleyDistance { seed, permutations: PermutationTable::new(seed) }
+    }
+}
+
+impl Default for WorleyDistance {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Seedable for WorleyDistance {
+    fn set_seed(self, seed: u32) -> Self {
+        Self::new(seed)
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+impl NoiseFn<f64, 2> for WorleyDistance {
+    fn get(&self, point: [f64; 2]) -> f64 {
+        worley_2d(&self.permutations, distance_functions::euclidean, ReturnType::Distance, Vector2::from(point))
+    }
+}
+
+impl NoiseFn<f64, 3> for WorleyDistance {
+    fn get(&self, point: [f64; 3]) -> f64 {
+        worley_3d(&self.permutations, distance_functions::euclidean, ReturnType::Distance, Vector3::from(point))
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use super::block::Block;
+use super::erosion::{ErosionRegions, ErosionSettings};
+use super::generator::{GeneratorConfig, GeneratorPass};
+use super::noise_layer::{DomainWarp, Fractal, NoiseKind, NoiseSettings};
+use super::road::{RoadNetwork, RoadSettings};
+use super::structure::StructureSettings;
+
+// Named world types that can be picked when creating a world
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WorldPreset {
+    Default,
+    Flat,
+    Spiral,
+    Arches,
+    Islands,
+    Amplified,
+}
+
+impl WorldPreset {
+    pub const ALL: [WorldPreset; 6] = [
+        WorldPreset::Default,
+        WorldPreset::Flat,
+        WorldPreset::Spiral,
+        WorldPreset::Arches,
+        WorldPreset::Islands,
+        WorldPreset::Amplified,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorldPreset::Default => "default",
+            WorldPreset::Flat => "flat",
+            WorldPreset::Spiral => "spiral",
+            WorldPreset::Arches => "arches",
+            WorldPreset::Islands => "islands",
+            WorldPreset::Amplified => "amplified",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn generator_config(&self, seed: u32) -> GeneratorConfig {
+        let flat_height = 0;
+        let base_layer = GeneratorPass::Flat { height: flat_height, block: Block::Dirt };
+
+        let passes = match self {
+            WorldPreset::Default => vec![
+                base_layer,
+                hills(seed, 10.0),
+                trees(seed),
+                mountains(seed, 60.0, 40.0),
+                roads(seed),
+                structures(seed),
+                floating_islands(seed, 90, 16),
+            ],
+            WorldPreset::Flat => vec![
+                GeneratorPass::Flat { height: flat_height, block: Block::DarkGrass },
+                trees(seed),
+                roads(seed),
+                structures(seed),
+            ],
+            WorldPreset::Spiral => vec![
+                base_layer,
+                GeneratorPass::Spiral {
+                    mountain_width: 2,
+                    scale: 0.01,
+                    height_scale: 20.0,
+                    blocks: (Block::DarkGrass, Block::Rock),
+                    spiral_factor: 5.0,
+                    num_rings: 1.0,
+                },
+            ],
+            WorldPreset::Arches => vec![
+                base_layer,
+                GeneratorPass::Arches {
+                    mountain_width: 2,
+                    height_scale: 2.0,
+                    blocks: (Block::DarkGrass, Block::Dirt),
+                    arch_factor: 1.0,
+                    num_arches: 4,
+                },
+            ],
+            // A sky biome, two layers of islands over low hills
+            WorldPreset::Islands => vec![
+                base_layer,
+                hills(seed, 6.0),
+                floating_islands(seed, 50, 20),
+                floating_islands(seed.wrapping_add(20), 100, 28),
+            ],
+            WorldPreset::Amplified => vec![
+                base_layer,
+                hills(seed, 30.0),
+                trees(seed),
+                mountains(seed, 140.0, 80.0),
+                roads(seed),
+                structures(seed),
+                floating_islands(seed, 180, 24),
+            ],
+        };
+
+        GeneratorConfig::new(seed, flat_height, passes)
+    }
+}
+
+fn hills(seed: u32, height_scale: f64) -> GeneratorPass {
+    GeneratorPass::Hills {
+        noise: NoiseSettings {
+            fractal: Fractal::Fbm,
+            seed,
+            frequency: 0.01,
+            octaves: 4,
+            ..Default::default()
+        },
+        height_scale,
+        base_height: 1,
+        block: Block::DarkGrass,
+    }
+}
+
+fn trees(seed: u32) -> GeneratorPass {
+    GeneratorPass::Trees {
+        noise: NoiseSettings {
+            seed,
+            frequency: 0.05, // Adjust this to change the distribution of trees
+            ..Default::default()
+        },
+        density: 0.1,
+        height: 13,
+        trunk_block: Block::Wood,
+        leaf_block: Block::DarkGrass,
+    }
+}
+
+fn mountains(seed: u32, height_scale: f64, warp_amplitude: f64) -> GeneratorPass {
+    GeneratorPass::Mountains {
+        noise: NoiseSettings {
+            fractal: Fractal::Ridged,
+            seed,
+            frequency: 0.01,
+            octaves: 5,
+            warp: Some(DomainWarp { frequency: 0.004, amplitude: warp_amplitude }),
+            ..Default::default()
+        },
+        erosion: Some(Arc::new(ErosionRegions::new(ErosionSettings {
+            seed: seed as u64,
+            ..Default::default()
+        }))),
+        height_scale,
+        upper_block: Block::DarkGrass,
+        lower_block: Block::Dirt,
+    }
+}
+
+fn structure_settings(seed: u32) -> StructureSettings {
+    StructureSettings {
+        seed: seed as u64,
+        ..Default::default()
+    }
+}
+
+fn structures(seed: u32) -> GeneratorPass {
+    GeneratorPass::Structures(structure_settings(seed))
+}
+
+// Roads lead to the structures of the same seed
+fn roads(seed: u32) -> GeneratorPass {
+    GeneratorPass::Roads(Arc::new(RoadNetwork::new(RoadSettings::default(), structure_settings(seed))))
+}
+
+fn floating_islands(seed: u32, altitude: i32, depth: i32) -> GeneratorPass {
+    GeneratorPass::FloatingIslands {
+        coverage: NoiseSettings {
+            kind: NoiseKind::OpenSimplex,
+            fractal: Fractal::Fbm,
+            seed: seed.wrapping_add(7),
+            frequency: 0.012,
+            octaves: 3,
+            ..Default::default()
+        },
+        shape: NoiseSettings {
+            fractal: Fractal::Fbm,
+            seed: seed.wrapping_add(8),
+            frequency: 0.06,
+            octaves: 3,
+            ..Default::default()
+        },
+        altitude,
+        depth,
+        surface_block: Block::LightGrass,
+        underside_block: Block::Rock,
+        waterfall_block: Block::Water,
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+
+// Results worked out once per fixed-size region of the world, shared between the chunk workers.
+// Once more than `capacity` regions are held the least recently used ones are dropped.
+pub struct RegionCache<T> {
+    capacity: usize,
+    entries: Mutex<RegionEntries<T>>,
+}
+
+// Filled in by the first thread to work a region out, any other thread asking for it meanwhile waits
+type Slot<T> = Arc<OnceLock<Arc<T>>>;
+
+struct RegionEntries<T> {
+    values: HashMap<(i32, i32), (Slot<T>, u64)>,
+    // Regions by the last time they were used, oldest first
+    recency: BTreeMap<u64, (i32, i32)>,
+    clock: u64,
+}
+
+impl<T> RegionEntries<T> {
+    fn touch(&mut self, region: (i32, i32)) -> Option<Slot<T>> {
+        let (value, used) = self.values.get_mut(&region)?;
+        self.recency.remove(used);
+        self.clock += 1;
+        *used = self.clock;
+        self.recency.insert(self.clock, region);
+        Some(value.clone())
+    }
+}
+
+impl<T> RegionCache<T> {
+    pub fn new(capacity: usize) -> Self {
+        RegionCache {
+            capacity,
+            entries: Mutex::new(RegionEntries {
+                values: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+            }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The cached result of a region, working it out with `compute` if it isn't held
+    pub fn get_or_insert_with(&self, region: (i32, i32), compute: impl FnOnce() -> T) -> Arc<T> {
+        let slot = self.slot(region);
+        // Compute outside the lock so other regions aren't held up, only the ones racing for this region wait
+        slot.get_or_init(|| Arc::new(compute())).clone()
+    }
+
+    fn slot(&self, region: (i32, i32)) -> Slot<T> {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(slot) = entries.touch(region) {
+            return slot;
+        }
+        let slot = Slot::default();
+        if self.capacity == 0 {
+            return slot;
+        }
+
+        while entries.values.len() >= self.capacity {
+            let Some((_, oldest)) = entries.recency.pop_first() else {
+                break;
+            };
+            entries.values.remove(&oldest);
+        }
+
+        entries.clock += 1;
+        let clock = entries.clock;
+        entries.recency.insert(clock, region);
+        entries.values.insert(region, (slot.clone(), clock));
+        slot
+    }
+}
@@ -0,0 +1,55 @@
+use super::coords::ChunkPos;
+
+// How far around the camera chunks are kept loaded, measured as a circle (a sphere once chunks stack vertically)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderDistance {
+    // Chunks within this many chunks of the camera chunk are loaded
+    pub load_radius: i32,
+    // Loaded chunks are only unloaded once they are this many chunks further out than the load radius,
+    // so moving back and forth over a chunk border doesn't load and unload the same chunks over and over
+    pub unload_margin: i32,
+}
+
+impl Default for RenderDistance {
+    fn default() -> Self {
+        RenderDistance::new(5)
+    }
+}
+
+impl RenderDistance {
+    pub const MIN_RADIUS: i32 = 2;
+    pub const MAX_RADIUS: i32 = 32;
+
+    pub fn new(load_radius: i32) -> Self {
+        RenderDistance {
+            load_radius: load_radius.clamp(Self::MIN_RADIUS, Self::MAX_RADIUS),
+            unload_margin: 2,
+        }
+    }
+
+    pub fn unload_radius(&self) -> i32 {
+        self.load_radius + self.unload_margin
+    }
+
+    pub fn should_load(&self, position: ChunkPos, camera_chunk: ChunkPos) -> bool {
+        chunk_distance_squared(position, camera_chunk) <= self.load_radius * self.load_radius
+    }
+
+    pub fn should_unload(&self, position: ChunkPos, camera_chunk: ChunkPos) -> bool {
+        chunk_distance_squared(position, camera_chunk) > self.unload_radius() * self.unload_radius()
+    }
+
+    // Every chunk position in the load radius around the camera chunk
+    pub fn chunks_to_load(&self, camera_chunk: ChunkPos) -> impl Iterator<Item = ChunkPos> + '_ {
+        let radius = self.load_radius;
+        (-radius..=radius)
+            .flat_map(move |x| (-radius..=radius).map(move |z| camera_chunk.offset(x, 0, z)))
+            .filter(move |&position| self.should_load(position, camera_chunk))
+    }
+}
+
+// Squared distance between two chunk positions, in chunks
+pub fn chunk_distance_squared(a: ChunkPos, b: ChunkPos) -> i32 {
+    let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+    dx * dx + dy * dy + dz * dz
+}
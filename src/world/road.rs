@@ -0,0 +1,489 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use cgmath::Point3;
+
+use crate::constants::world::CHUNK_SIZE;
+
+use super::block::Block;
+use super::heightfield::HeightField;
+use super::region_cache::RegionCache;
+use super::structure::{structures_in_cell, PieceKind, StructurePiece, StructureSettings};
+use super::voxels::ChunkVoxels;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoadSettings {
+    // Structure cells per side of the regions roads are planned in
+    pub region_cells: i32,
+    // Blocks between the nodes of the pathfinding grid
+    pub grid_step: i32,
+    // Width of the road in blocks
+    pub width: i32,
+    // How strongly the pathfinder avoids climbing, 0 ignores the slope
+    pub slope_weight: f64,
+    // Steepest rise between two grid nodes a road can take
+    pub max_step_rise: i32,
+    // Furthest the ground is cut away or filled in to even out a road
+    pub max_flatten: i32,
+    // Number of points along the road its height is averaged over
+    pub smoothing: usize,
+    // Planned regions kept around, the least recently used ones are dropped past this
+    pub max_cached_regions: usize,
+}
+
+impl Default for RoadSettings {
+    fn default() -> Self {
+        RoadSettings {
+            region_cells: 4,
+            grid_step: 4,
+            width: 3,
+            slope_weight: 6.0,
+            max_step_rise: 6,
+            max_flatten: 2,
+            smoothing: 7,
+            max_cached_regions: 64,
+        }
+    }
+}
+
+// One column of a road's centre line, at the height the road surface is levelled to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoadPoint {
+    pub x: i32,
+    pub z: i32,
+    pub height: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Road {
+    pub points: Vec<RoadPoint>,
+}
+
+impl Road {
+    // Whether any column the road covers lies in a chunk
+    pub fn intersects_chunk(&self, chunk_position: Point3<i32>, width: i32) -> bool {
+        let reach = width / 2;
+        let chunk_min_x = chunk_position.x * CHUNK_SIZE;
+        let chunk_min_z = chunk_position.z * CHUNK_SIZE;
+
+        self.points.iter().any(|point| {
+            point.x + reach >= chunk_min_x
+                && point.x - reach < chunk_min_x + CHUNK_SIZE
+                && point.z + reach >= chunk_min_z
+                && point.z - reach < chunk_min_z + CHUNK_SIZE
+        })
+    }
+}
+
+// Square area of the world, `max` is exclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_x: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_z: i32,
+}
+
+impl Bounds {
+    // The smallest bounds covering both
+    pub fn union(self, other: Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_z: self.min_z.min(other.min_z),
+            max_x: self.max_x.max(other.max_x),
+            max_z: self.max_z.max(other.max_z),
+        }
+    }
+
+    // The part of both bounds they have in common
+    pub fn intersection(self, other: Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.max(other.min_x),
+            min_z: self.min_z.max(other.min_z),
+            max_x: self.max_x.min(other.max_x),
+            max_z: self.max_z.min(other.max_z),
+        }
+    }
+}
+
+// Furthest a road joining two regions strays outside the box around its two ends
+const CONNECTION_DETOUR: i32 = 64;
+
+// Plans the roads between the structures of fixed-size regions and keeps them around,
+// so a region is only planned once no matter how many of its chunks are generated.
+// The roads inside a region never leave it. Each region also has roads joining it to its +x and +z neighbours,
+// so a chunk only needs the roads inside its own region and the joining roads of it and its -x and -z neighbours.
+pub struct RoadNetwork {
+    settings: RoadSettings,
+    structures: StructureSettings,
+    regions: RegionCache<Vec<Road>>,
+    connections: RegionCache<Vec<Road>>,
+}
+
+impl RoadNetwork {
+    pub fn new(settings: RoadSettings, structures: StructureSettings) -> Self {
+        RoadNetwork {
+            settings,
+            structures,
+            regions: RegionCache::new(settings.max_cached_regions),
+            connections: RegionCache::new(settings.max_cached_regions),
+        }
+    }
+
+    pub fn settings(&self) -> &RoadSettings {
+        &self.settings
+    }
+
+    // The structures the roads lead to
+    pub fn structures(&self) -> &StructureSettings {
+        &self.structures
+    }
+
+    pub fn cached_regions(&self) -> usize {
+        self.regions.len()
+    }
+
+    // Blocks per side of a region
+    pub fn region_size(&self) -> i32 {
+        self.settings.region_cells * self.structures.cell_size
+    }
+
+    pub fn region_bounds(&self, region: (i32, i32)) -> Bounds {
+        let region_size = self.region_size();
+        Bounds {
+            min_x: region.0 * region_size,
+            min_z: region.1 * region_size,
+            max_x: (region.0 + 1) * region_size,
+            max_z: (region.1 + 1) * region_size,
+        }
+    }
+
+    // The roads inside a region and the ones joining it to its +x and +z neighbours
+    pub fn region_roads(&self, surface: &HeightField, region: (i32, i32)) -> Vec<Road> {
+        [self.roads_inside(surface, region), self.connections(surface, region)].concat()
+    }
+
+    fn roads_inside(&self, surface: &HeightField, region: (i32, i32)) -> Vec<Road> {
+        self.regions.get_or_insert_with(region, || self.plan_region(surface, region)).to_vec()
+    }
+
+    fn connections(&self, surface: &HeightField, region: (i32, i32)) -> Vec<Road> {
+        self.connections.get_or_insert_with(region, || self.plan_connections(surface, region)).to_vec()
+    }
+
+    // Every road reaching into a chunk
+    pub fn roads_intersecting(&self, chunk_position: Point3<i32>, surface: &HeightField) -> Vec<Road> {
+        let region_size = self.region_size();
+        let region = (
+            (chunk_position.x * CHUNK_SIZE).div_euclid(region_size),
+            (chunk_position.z * CHUNK_SIZE).div_euclid(region_size),
+        );
+
+        let mut roads = self.roads_inside(surface, region);
+        for region in [region, (region.0 - 1, region.1), (region.0, region.1 - 1)] {
+            roads.extend(self.connections(surface, region));
+        }
+        roads.retain(|road| road.intersects_chunk(chunk_position, self.settings.width));
+        roads
+    }
+
+    // Points of interest in a region, joined up by their shortest connections
+    fn plan_region(&self, surface: &HeightField, region: (i32, i32)) -> Vec<Road> {
+        let bounds = self.region_bounds(region);
+        let points = points_of_interest(&self.structures, self.settings.region_cells, region, surface);
+
+        spanning_tree(&points)
+            .into_iter()
+            .filter_map(|(from, to)| plan_road(surface, points[from], points[to], bounds, &self.settings))
+            .collect()
+    }
+
+    // A road between the closest points of a region and each of its +x and +z neighbours, so the networks
+    // of neighbouring regions join up. Only the cells along their shared border are looked at.
+    fn plan_connections(&self, surface: &HeightField, region: (i32, i32)) -> Vec<Road> {
+        let cells = self.settings.region_cells;
+        // Points of a line of a region's worth of cells, starting at a cell
+        let points_along = |start: (i32, i32), step: (i32, i32)| {
+            let line = (0..cells).map(move |i| (start.0 + step.0 * i, start.1 + step.1 * i));
+            cell_points(&self.structures, line, surface)
+        };
+        let (first_x, first_z) = (region.0 * cells, region.1 * cells);
+
+        let mut roads = Vec::new();
+        // The last column of cells against the neighbour's first one, then the same for the rows
+        for (neighbour, last, first, step) in [
+            ((region.0 + 1, region.1), (first_x + cells - 1, first_z), (first_x + cells, first_z), (0, 1)),
+            ((region.0, region.1 + 1), (first_x, first_z + cells - 1), (first_x, first_z + cells), (1, 0)),
+        ] {
+            let Some((from, to)) = closest_pair(&points_along(last, step), &points_along(first, step)) else {
+                continue;
+            };
+
+            let around = Bounds {
+                min_x: from.0.min(to.0) - CONNECTION_DETOUR,
+                min_z: from.1.min(to.1) - CONNECTION_DETOUR,
+                max_x: from.0.max(to.0) + CONNECTION_DETOUR,
+                max_z: from.1.max(to.1) + CONNECTION_DETOUR,
+            };
+            let bounds = self.region_bounds(region).union(self.region_bounds(neighbour)).intersection(around);
+            roads.extend(plan_road(surface, from, to, bounds, &self.settings));
+        }
+        roads
+    }
+}
+
+// Where roads lead to in a region, the front door of every structure standing on the surface
+pub fn points_of_interest(structures: &StructureSettings, region_cells: i32, region: (i32, i32), surface: &HeightField) -> Vec<(i32, i32)> {
+    let cells_x = region.0 * region_cells..(region.0 + 1) * region_cells;
+    let cells_z = region.1 * region_cells..(region.1 + 1) * region_cells;
+    cell_points(structures, cells_x.flat_map(|cell_x| cells_z.clone().map(move |cell_z| (cell_x, cell_z))), surface)
+}
+
+fn cell_points(structures: &StructureSettings, cells: impl Iterator<Item = (i32, i32)>, surface: &HeightField) -> Vec<(i32, i32)> {
+    cells
+        .filter_map(|(cell_x, cell_z)| {
+            // The first piece is the heart of the structure, a village's well
+            structures_in_cell(structures, cell_x, cell_z, surface).first().map(entrance)
+        })
+        .collect()
+}
+
+fn entrance(piece: &StructurePiece) -> (i32, i32) {
+    let center_x = piece.min.x + piece.size.x / 2;
+    match piece.kind {
+        // Doors are on the low z side
+        PieceKind::Tower | PieceKind::House => (center_x, piece.min.z - 2),
+        _ => (center_x, piece.min.z + piece.size.z / 2),
+    }
+}
+
+fn squared_distance(a: (i32, i32), b: (i32, i32)) -> i64 {
+    ((a.0 - b.0) as i64).pow(2) + ((a.1 - b.1) as i64).pow(2)
+}
+
+// The closest two points, one from each side
+fn closest_pair(first: &[(i32, i32)], second: &[(i32, i32)]) -> Option<((i32, i32), (i32, i32))> {
+    first
+        .iter()
+        .flat_map(|&a| second.iter().map(move |&b| (a, b)))
+        .min_by_key(|&(a, b)| squared_distance(a, b))
+}
+
+// Edges of the minimum spanning tree between points, so every point is reachable with the least road
+fn spanning_tree(points: &[(i32, i32)]) -> Vec<(usize, usize)> {
+    let distance = |a: (i32, i32), b: (i32, i32)| squared_distance(a, b) as f64;
+    let mut edges = Vec::new();
+    if points.is_empty() {
+        return edges;
+    }
+
+    let mut in_tree = vec![false; points.len()];
+    // Closest tree point of every point not in the tree yet
+    let mut closest: Vec<(f64, usize)> = points.iter().map(|&point| (distance(point, points[0]), 0)).collect();
+    in_tree[0] = true;
+
+    for _ in 1..points.len() {
+        let next = (0..points.len())
+            .filter(|&index| !in_tree[index])
+            .min_by(|&a, &b| closest[a].0.total_cmp(&closest[b].0))
+            .unwrap();
+        in_tree[next] = true;
+        edges.push((closest[next].1, next));
+
+        for index in 0..points.len() {
+            let to_next = distance(points[index], points[next]);
+            if !in_tree[index] && to_next < closest[index].0 {
+                closest[index] = (to_next, next);
+            }
+        }
+    }
+    edges
+}
+
+// Open node of the pathfinder, ordered so the heap pops the lowest estimate first
+#[derive(PartialEq)]
+struct OpenNode {
+    estimate: f64,
+    node: usize,
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Find a road between two columns with A* over a coarse grid of the bounds, climbing costs extra and
+// rises steeper than `max_step_rise` can't be taken at all. Returns `None` if the ground doesn't allow a road.
+pub fn plan_road(surface: &HeightField, from: (i32, i32), to: (i32, i32), bounds: Bounds, settings: &RoadSettings) -> Option<Road> {
+    let step = settings.grid_step;
+    // Nodes stay far enough inside the bounds for the whole road width to fit
+    let inset = settings.width / 2 + 1;
+    let origin_x = bounds.min_x + inset;
+    let origin_z = bounds.min_z + inset;
+    let columns = ((bounds.max_x - inset - origin_x) / step).max(1);
+    let rows = ((bounds.max_z - inset - origin_z) / step).max(1);
+
+    let node_position = |node: usize| (origin_x + (node as i32 / rows) * step, origin_z + (node as i32 % rows) * step);
+    let nearest_node = |(x, z): (i32, i32)| {
+        let column = ((x - origin_x + step / 2) / step).clamp(0, columns - 1);
+        let row = ((z - origin_z + step / 2) / step).clamp(0, rows - 1);
+        (column * rows + row) as usize
+    };
+
+    // Ground under the nodes, only sampled once the search reaches them
+    let mut heights: Vec<Option<i32>> = vec![None; (columns * rows) as usize];
+    let mut height = |node: usize| {
+        *heights[node].get_or_insert_with(|| {
+            let (x, z) = node_position(node);
+            surface.sample(x, z)
+        })
+    };
+
+    let start = nearest_node(from);
+    let goal = nearest_node(to);
+    let (goal_x, goal_z) = node_position(goal);
+    let heuristic = |node: usize| {
+        let (x, z) = node_position(node);
+        (((x - goal_x) as f64).powi(2) + ((z - goal_z) as f64).powi(2)).sqrt()
+    };
+
+    let mut cost = vec![f64::INFINITY; (columns * rows) as usize];
+    let mut came_from = vec![usize::MAX; (columns * rows) as usize];
+    let mut open = BinaryHeap::new();
+    cost[start] = 0.0;
+    open.push(OpenNode { estimate: heuristic(start), node: start });
+
+    while let Some(OpenNode { estimate, node }) = open.pop() {
+        if node == goal {
+            break;
+        }
+        if estimate > cost[node] + heuristic(node) {
+            continue; // Stale entry, the node was reached cheaper since
+        }
+
+        let column = node as i32 / rows;
+        let row = node as i32 % rows;
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let (next_column, next_row) = (column + dx, row + dz);
+            if next_column < 0 || next_row < 0 || next_column >= columns || next_row >= rows {
+                continue;
+            }
+
+            let next = (next_column * rows + next_row) as usize;
+            let rise = (height(next) - height(node)).abs();
+            if rise > settings.max_step_rise {
+                continue;
+            }
+
+            let distance = (((dx * dx + dz * dz) as f64).sqrt()) * step as f64;
+            let slope = rise as f64 / distance;
+            let next_cost = cost[node] + distance * (1.0 + settings.slope_weight * slope * slope);
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                came_from[next] = node;
+                open.push(OpenNode { estimate: next_cost + heuristic(next), node: next });
+            }
+        }
+    }
+
+    if start != goal && came_from[goal] == usize::MAX {
+        return None;
+    }
+
+    // Walk back from the goal, then trace the corners of the path out block by block
+    let mut corners = vec![to];
+    let mut node = goal;
+    while node != start {
+        corners.push(node_position(node));
+        node = came_from[node];
+    }
+    corners.push(node_position(start));
+    corners.push(from);
+    corners.reverse();
+
+    let mut columns_along = Vec::new();
+    for pair in corners.windows(2) {
+        trace_line(pair[0], pair[1], &mut columns_along);
+    }
+    columns_along.dedup();
+
+    // Level the road out by averaging the ground height along it
+    let ground: Vec<i32> = columns_along.iter().map(|&(x, z)| surface.sample(x, z)).collect();
+    let half_window = settings.smoothing / 2;
+    let points = columns_along
+        .iter()
+        .enumerate()
+        .map(|(index, &(x, z))| {
+            let window = &ground[index.saturating_sub(half_window)..(index + half_window + 1).min(ground.len())];
+            let height = (window.iter().sum::<i32>() as f64 / window.len() as f64).round() as i32;
+            RoadPoint { x, z, height }
+        })
+        .collect();
+
+    Some(Road { points })
+}
+
+// Every column on the straight line between two columns, including both ends
+fn trace_line(from: (i32, i32), to: (i32, i32), columns: &mut Vec<(i32, i32)>) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+    for i in 0..=steps {
+        let t = i as f64 / steps as f64;
+        let x = from.0 as f64 + (to.0 - from.0) as f64 * t;
+        let z = from.1 as f64 + (to.1 - from.1) as f64 * t;
+        columns.push((x.round() as i32, z.round() as i32));
+    }
+}
+
+// Lay the part of the roads inside a chunk, the ground is cut or filled by at most `max_flatten`
+// to meet the road height and the road gets some headroom cleared above it
+pub fn build_roads(chunk_position: Point3<i32>, roads: &[Road], settings: &RoadSettings, surface: &HeightField, voxels: &mut ChunkVoxels) {
+    let headroom = 3;
+    let reach = settings.width / 2;
+    let chunk_min_x = chunk_position.x * CHUNK_SIZE;
+    let chunk_min_z = chunk_position.z * CHUNK_SIZE;
+
+    // Road height of every chunk column the roads cover, where roads cross the lowest one wins
+    let mut road_heights: Vec<Option<i32>> = vec![None; (CHUNK_SIZE * CHUNK_SIZE) as usize];
+    for point in roads.iter().flat_map(|road| road.points.iter()) {
+        for dx in -reach..=reach {
+            for dz in -reach..=reach {
+                let (x, z) = (point.x + dx - chunk_min_x, point.z + dz - chunk_min_z);
+                if !(0..CHUNK_SIZE).contains(&x) || !(0..CHUNK_SIZE).contains(&z) {
+                    continue;
+                }
+
+                let road_height = &mut road_heights[(x * CHUNK_SIZE + z) as usize];
+                *road_height = Some(road_height.map_or(point.height, |height| height.min(point.height)));
+            }
+        }
+    }
+
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let Some(road_height) = road_heights[(x * CHUNK_SIZE + z) as usize] else {
+                continue;
+            };
+
+            let ground = surface.sample(chunk_min_x + x, chunk_min_z + z);
+            let road_height = road_height.clamp(ground - settings.max_flatten, ground + settings.max_flatten).max(0);
+
+            // Fill in the dips, then cut away anything above the road
+            for y in ground + 1..road_height {
+                voxels.set(x, y, z, Block::Dirt);
+            }
+            for y in road_height + 1..=ground.max(road_height) + headroom {
+                voxels.set(x, y, z, Block::Air);
+            }
+            voxels.set(x, road_height, z, Block::Path);
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use cgmath::{Point3, Vector3};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::constants::world::CHUNK_SIZE;
+
+use super::block::Block;
+use super::heightfield::HeightField;
+use super::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+// Kinds of structure the world can place
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StructureKind {
+    Ruin,
+    Tower,
+    DungeonRoom,
+    Village,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 4] = [
+        StructureKind::Ruin,
+        StructureKind::Tower,
+        StructureKind::DungeonRoom,
+        StructureKind::Village,
+    ];
+}
+
+// The building blocks structures are made of, a village is a well with houses around it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    RuinWalls,
+    Tower,
+    DungeonRoom,
+    House,
+    Well,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StructureSettings {
+    pub seed: u64,
+    // Side of the square cells the world is split into, each cell holds at most one structure.
+    // Structures that don't fit in a cell this small are left out.
+    pub cell_size: i32,
+    // Chance of a cell holding a structure
+    pub chance: f64,
+}
+
+impl Default for StructureSettings {
+    fn default() -> Self {
+        StructureSettings {
+            seed: 142,
+            cell_size: 96,
+            chance: 0.6,
+        }
+    }
+}
+
+// Pieces keep this far away from the edges of their cell
+const CELL_MARGIN: i32 = 4;
+// Air cleared above surface pieces so terrain doesn't cut through them
+const CLEARANCE: i32 = 4;
+// Ground left above the ceiling of dungeon rooms
+const DUNGEON_COVER: i32 = 3;
+
+// One box of blocks of a structure, in world block coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StructurePiece {
+    pub structure: StructureKind,
+    pub kind: PieceKind,
+    // Lowest corner of the box, the piece's floor is at `min.y`
+    pub min: Point3<i32>,
+    pub size: Vector3<i32>,
+    // Varies the details of the template, like how far ruin walls have crumbled
+    pub seed: u64,
+}
+
+impl StructurePiece {
+    // One past the highest corner of the box
+    pub fn max(&self) -> Point3<i32> {
+        self.min + self.size
+    }
+
+    pub fn is_underground(&self) -> bool {
+        self.kind == PieceKind::DungeonRoom
+    }
+
+    pub fn intersects_chunk(&self, chunk_position: Point3<i32>) -> bool {
+        let chunk_min_x = chunk_position.x * CHUNK_SIZE;
+        let chunk_min_z = chunk_position.z * CHUNK_SIZE;
+
+        self.min.x < chunk_min_x + CHUNK_SIZE
+            && self.max().x > chunk_min_x
+            && self.min.z < chunk_min_z + CHUNK_SIZE
+            && self.max().z > chunk_min_z
+    }
+
+    fn overlaps(&self, other: &StructurePiece) -> bool {
+        self.min.x < other.max().x && other.min.x < self.max().x && self.min.z < other.max().z && other.min.z < self.max().z
+    }
+
+    // Block the piece puts at a world position, `None` leaves whatever is there
+    pub fn block_at(&self, position: Point3<i32>) -> Option<Block> {
+        let local = position - self.min;
+        let (x, y, z) = (local.x, local.y, local.z);
+        if x < 0 || y < 0 || z < 0 || x >= self.size.x || y >= self.size.y || z >= self.size.z {
+            return None;
+        }
+
+        let (width, height, depth) = (self.size.x, self.size.y, self.size.z);
+        let on_wall = x == 0 || z == 0 || x == width - 1 || z == depth - 1;
+        let on_corner = (x == 0 || x == width - 1) && (z == 0 || z == depth - 1);
+        let hash = block_hash(self.seed, x, y, z);
+
+        match self.kind {
+            PieceKind::RuinWalls => {
+                if y == 0 {
+                    return Some(if hash.is_multiple_of(3) { Block::Dirt } else { Block::Rock });
+                }
+
+                // Every wall column has crumbled down to its own height
+                let standing = block_hash(self.seed, x, 0, z) % height as u64;
+                (on_wall && (y as u64) <= standing).then_some(if hash.is_multiple_of(4) { Block::Dirt } else { Block::Rock })
+            }
+            PieceKind::Tower => {
+                let door = z == 0 && x == width / 2 && (1..=2).contains(&y);
+                let window = y % 4 == 2 && (x == width / 2 || z == depth / 2);
+                let battlements = y == height - 1;
+
+                if battlements {
+                    (on_wall && (x + z) % 2 == 0).then_some(Block::Rock)
+                } else if on_wall {
+                    Some(if door || window { Block::Air } else { Block::Rock })
+                } else if y % 5 == 0 {
+                    Some(Block::Wood)
+                } else {
+                    Some(Block::Air)
+                }
+            }
+            PieceKind::DungeonRoom => {
+                let shell = on_wall || y == 0 || y == height - 1;
+                if shell {
+                    Some(Block::Rock)
+                } else if y == 1 && hash.is_multiple_of(23) {
+                    Some(Block::Wood) // Crates left lying around
+                } else {
+                    Some(Block::Air)
+                }
+            }
+            PieceKind::House => {
+                let roof = height - 2;
+                if y == 0 {
+                    Some(Block::Wood)
+                } else if y < roof {
+                    let door = z == 0 && x == width / 2 && y <= 2;
+                    let window = y == 2 && (x == 0 || x == width - 1) && z == depth / 2;
+                    Some(if on_wall && !door && !window { Block::Wood } else { Block::Air })
+                } else if y == roof {
+                    Some(Block::Dirt)
+                } else {
+                    // Narrower ridge on top of the roof
+                    let ridge = x > 0 && x < width - 1 && z > 0 && z < depth - 1;
+                    ridge.then_some(Block::Dirt)
+                }
+            }
+            PieceKind::Well => match y {
+                0 => Some(Block::Rock),
+                1 => Some(if on_wall { Block::Rock } else { Block::Water }),
+                _ if y == height - 1 => Some(Block::Wood),
+                _ => Some(if on_corner { Block::Wood } else { Block::Air }),
+            },
+        }
+    }
+
+    // Write the part of the piece that falls inside a chunk, including its foundation and clearance
+    pub fn place(&self, chunk_position: Point3<i32>, voxels: &mut ChunkVoxels) {
+        let chunk_min_x = chunk_position.x * CHUNK_SIZE;
+        let chunk_min_z = chunk_position.z * CHUNK_SIZE;
+        let max = self.max();
+
+        for world_x in self.min.x.max(chunk_min_x)..max.x.min(chunk_min_x + CHUNK_SIZE) {
+            for world_z in self.min.z.max(chunk_min_z)..max.z.min(chunk_min_z + CHUNK_SIZE) {
+                let (x, z) = (world_x - chunk_min_x, world_z - chunk_min_z);
+
+                if !self.is_underground() {
+                    // Fill the ground in under the floor so the piece doesn't hang over slopes
+                    let mut y = self.min.y - 1;
+                    while y >= 0 && !voxels.get(x, y, z).is_opaque() {
+                        voxels.set(x, y, z, Block::Rock);
+                        y -= 1;
+                    }
+
+                    // Clear the terrain out of the way above the floor
+                    for y in self.min.y..(max.y + CLEARANCE).min(CHUNK_HEIGHT) {
+                        voxels.set(x, y, z, Block::Air);
+                    }
+                }
+
+                for y in self.min.y..max.y {
+                    if let Some(block) = self.block_at(Point3::new(world_x, y, world_z)) {
+                        voxels.set(x, y, z, block);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Every piece of the structure in a cell, pieces never leave the cell they belong to
+pub fn structures_in_cell(settings: &StructureSettings, cell_x: i32, cell_z: i32, surface: &HeightField) -> Vec<StructurePiece> {
+    let mut rng = StdRng::seed_from_u64(cell_seed(settings.seed, cell_x, cell_z));
+    if rng.gen::<f64>() >= settings.chance {
+        return Vec::new();
+    }
+
+    let cell = CellPlanner {
+        min_x: cell_x * settings.cell_size + CELL_MARGIN,
+        min_z: cell_z * settings.cell_size + CELL_MARGIN,
+        max_x: (cell_x + 1) * settings.cell_size - CELL_MARGIN,
+        max_z: (cell_z + 1) * settings.cell_size - CELL_MARGIN,
+        surface,
+    };
+
+    let structure = StructureKind::ALL[rng.gen_range(0..StructureKind::ALL.len())];
+    match structure {
+        StructureKind::Ruin => {
+            let size = Vector3::new(rng.gen_range(7..=11), 5, rng.gen_range(7..=11));
+            cell.surface_piece(&mut rng, structure, PieceKind::RuinWalls, size).into_iter().collect()
+        }
+        StructureKind::Tower => {
+            let size = Vector3::new(7, rng.gen_range(12..=20), 7);
+            cell.surface_piece(&mut rng, structure, PieceKind::Tower, size).into_iter().collect()
+        }
+        StructureKind::DungeonRoom => {
+            let size = Vector3::new(rng.gen_range(7..=11), 6, rng.gen_range(7..=11));
+            let Some(mut room) = cell.surface_piece(&mut rng, structure, PieceKind::DungeonRoom, size) else {
+                return Vec::new();
+            };
+            let (center_x, center_z) = (room.min.x + size.x / 2, room.min.z + size.z / 2);
+            room.min.y = surface.sample(center_x, center_z) - DUNGEON_COVER - size.y + 1;
+
+            // Too little ground to dig into, leave a ruin behind instead
+            if room.min.y < 1 {
+                room.structure = StructureKind::Ruin;
+                room.kind = PieceKind::RuinWalls;
+                room.size.y = 5;
+                room.min.y = surface.sample(center_x, center_z) + 1;
+            }
+            vec![room]
+        }
+        StructureKind::Village => cell.village(&mut rng),
+    }
+}
+
+// Every structure piece that reaches into a chunk, only the cells overlapping the chunk are looked at
+pub fn structures_intersecting(settings: &StructureSettings, chunk_position: Point3<i32>, surface: &HeightField) -> Vec<StructurePiece> {
+    let chunk_min_x = chunk_position.x * CHUNK_SIZE;
+    let chunk_min_z = chunk_position.z * CHUNK_SIZE;
+    let first_cell_x = chunk_min_x.div_euclid(settings.cell_size);
+    let first_cell_z = chunk_min_z.div_euclid(settings.cell_size);
+    let last_cell_x = (chunk_min_x + CHUNK_SIZE - 1).div_euclid(settings.cell_size);
+    let last_cell_z = (chunk_min_z + CHUNK_SIZE - 1).div_euclid(settings.cell_size);
+
+    let mut pieces = Vec::new();
+    for cell_x in first_cell_x..=last_cell_x {
+        for cell_z in first_cell_z..=last_cell_z {
+            pieces.extend(
+                structures_in_cell(settings, cell_x, cell_z, surface)
+                    .into_iter()
+                    .filter(|piece| piece.intersects_chunk(chunk_position)),
+            );
+        }
+    }
+    pieces
+}
+
+// Usable area of a cell and the ground height the pieces sit on
+struct CellPlanner<'a, 'b> {
+    min_x: i32,
+    min_z: i32,
+    max_x: i32,
+    max_z: i32,
+    surface: &'a HeightField<'b>,
+}
+
+impl CellPlanner<'_, '_> {
+    // A piece standing on the ground at its middle, anywhere in the cell, if the cell is big enough for it
+    fn surface_piece(&self, rng: &mut StdRng, structure: StructureKind, kind: PieceKind, size: Vector3<i32>) -> Option<StructurePiece> {
+        if self.max_x - size.x < self.min_x || self.max_z - size.z < self.min_z {
+            return None;
+        }
+        let x = rng.gen_range(self.min_x..=self.max_x - size.x);
+        let z = rng.gen_range(self.min_z..=self.max_z - size.z);
+        Some(self.piece_at(rng, structure, kind, x, z, size))
+    }
+
+    fn piece_at(&self, rng: &mut StdRng, structure: StructureKind, kind: PieceKind, x: i32, z: i32, size: Vector3<i32>) -> StructurePiece {
+        let ground = self.surface.sample(x + size.x / 2, z + size.z / 2);
+        StructurePiece {
+            structure,
+            kind,
+            min: Point3::new(x, ground + 1, z),
+            size,
+            seed: rng.gen(),
+        }
+    }
+
+    // A well in the middle with houses spread around it, every house follows the ground on its own
+    fn village(&self, rng: &mut StdRng) -> Vec<StructurePiece> {
+        let well_size = Vector3::new(3, 5, 3);
+        let reach = 20; // How far from the well houses are built
+        if self.max_x - self.min_x < 2 * reach + well_size.x || self.max_z - self.min_z < 2 * reach + well_size.z {
+            return Vec::new();
+        }
+        let well_x = rng.gen_range(self.min_x + reach..=self.max_x - reach - well_size.x);
+        let well_z = rng.gen_range(self.min_z + reach..=self.max_z - reach - well_size.z);
+        let mut pieces = vec![self.piece_at(rng, StructureKind::Village, PieceKind::Well, well_x, well_z, well_size)];
+
+        let houses = rng.gen_range(2..=5);
+        for _ in 0..houses {
+            let size = Vector3::new(rng.gen_range(5..=7), 6, rng.gen_range(5..=7));
+            let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+            let distance = rng.gen_range(8.0..(reach - 7) as f64);
+            let x = well_x + (angle.cos() * distance) as i32 - size.x / 2;
+            let z = well_z + (angle.sin() * distance) as i32 - size.z / 2;
+
+            let house = self.piece_at(rng, StructureKind::Village, PieceKind::House, x, z, size);
+            if pieces.iter().all(|piece| !piece.overlaps(&house)) {
+                pieces.push(house);
+            }
+        }
+        pieces
+    }
+}
+
+// Seed mixed from the world seed and a cell, or a single world column
+pub(super) fn cell_seed(seed: u64, cell_x: i32, cell_z: i32) -> u64 {
+    let mut hash = seed ^ (cell_x as u32 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    hash ^= (cell_z as u32 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^ (hash >> 32)
+}
+
+// Cheap deterministic hash of a block inside a piece
+fn block_hash(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+    let mut hash = seed
+        ^ (x as u64).wrapping_mul(0x27d4_eb2d)
+        ^ (y as u64).wrapping_mul(0x1656_67b1)
+        ^ (z as u64).wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    hash ^ (hash >> 29)
+}
@@ -1,260 +1,209 @@
-use cgmath::{Point3, Vector3};
-use noise::{Perlin, NoiseFn};
-use rand::Rng;
+use cgmath::Point3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::f64::consts::PI;
 
 
-use crate::{constants::world::{CHUNK_SIZE, CUBE_INDICES, OVERLAP}, graphics::cube::{create_single_tx_cube_vertices, Vertex}, shapes::cube::create_cube};
+use crate::constants::world::CHUNK_SIZE;
 
-pub fn generate_flat_terrain(flat_height: i32, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, texture_id: u32) {
+use super::block::Block;
+use super::erosion::ErosionRegions;
+use super::heightfield::{column_world_position, HeightField};
+use super::noise_layer::NoiseLayer;
+use super::structure::cell_seed;
+use super::voxels::ChunkVoxels;
+
+pub fn generate_flat_terrain(flat_height: i32, voxels: &mut ChunkVoxels, block: Block) {
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
             for y in 0..= flat_height {  // Ensure the flat terrain is generated up to the specified height
-                let offset = Vector3::new(x as f32, y as f32, z as f32);
-              
-                let (cube_vertices, cube_indices) = create_cube(offset, texture_id);
-
-                // generate vertices and indices for each vertex
-                let base_index = vertices.len() as u32;
-                vertices.extend_from_slice(&cube_vertices);
-
-
-                let adjusted_indices: Vec<u32> = cube_indices
-                    .iter()
-                    .map(|&idx| idx + base_index)
-                    .collect();
-                indices.extend_from_slice(&adjusted_indices);
+                voxels.set(x, y, z, block);
             }
         }}
 }
 
+// Height of the mountain surface in a world column, eroding the noise first if asked to
+pub fn mountain_height(
+    noise: &NoiseLayer,
+    erosion: Option<&ErosionRegions>,
+    height_scale: f64,
+    flat_height: i32,
+    world_x: i32,
+    world_z: i32,
+) -> i32 {
+    let base_height = |world_x: i32, world_z: i32| noise.get(world_x as f64, world_z as f64) * height_scale;
+    let height = match erosion {
+        Some(erosion) => erosion.height(&base_height, world_x, world_z),
+        None => base_height(world_x, world_z),
+    };
+    height.round() as i32 + flat_height
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_mountainous_terrain(
     chunk_position: Point3<i32>,
     flat_height: i32,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
-    perlin: Perlin,
-    scale: f64,
+    voxels: &mut ChunkVoxels,
+    noise: &NoiseLayer,
+    erosion: Option<&ErosionRegions>,
     height_scale: f64,
-    extended_size: i32,
-    upper_texture_id: u32,
-    lower_texture_id: u32,
-    _lower_portion_height: i32, // No longer used for texture selection
+    upper_block: Block,
+    lower_block: Block,
+    seed: u64,
 ) {
-    let mut height_map = vec![vec![0; extended_size as usize]; extended_size as usize];
-    let mut rng = rand::thread_rng(); // Initialize random number generator
-
-    // Generate Perlin noise for the height map
-    for x in 0..extended_size {
-        for z in 0..extended_size {
-            let world_x = (chunk_position.x * CHUNK_SIZE + x) as f64;
-            let world_z = (chunk_position.z * CHUNK_SIZE + z) as f64;
-
-            let noise_value = perlin.get([world_x * scale, world_z * scale]);
-            let height = (noise_value * height_scale).round() as i32 + flat_height;
-            height_map[x as usize][z as usize] = height;
-        }
-    }
+    let height_field = HeightField::new(|world_x, world_z| {
+        mountain_height(noise, erosion, height_scale, flat_height, world_x, world_z)
+    });
 
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
-            let base_height = height_map[(x + OVERLAP) as usize][(z + OVERLAP) as usize];
+            let (world_x, world_z) = column_world_position(chunk_position, x, z);
+            let base_height = height_field.sample(world_x, world_z);
+            // Seeded by the column, so the chunk comes out the same every time it's generated
+            let mut rng = StdRng::seed_from_u64(cell_seed(seed, world_x, world_z));
 
             for y in (flat_height + 1)..=base_height {
-                if is_block_exposed(x, y, z, &height_map) {
-                    let offset = Vector3::new(x as f32, y as f32, z as f32);
-
-                    // Randomly determine which texture to use
-                    let random_chance: f32 = rng.gen(); // Generate a random float between 0.0 and 1.0
-                    let texture_id = if random_chance < 0.08 {
-                        // 8% chance to apply the secondary texture
-                        lower_texture_id
-                    } else {
-                        upper_texture_id
-                    };
-
-                   let (cube_vertices, cube_indices) = create_cube(offset, texture_id);
-
-                    let base_index = vertices.len() as u32;
-                    vertices.extend_from_slice(&cube_vertices);
-
-                    let adjusted_indices: Vec<u32> = cube_indices.iter()
-                        .map(|&idx| idx as u32 + base_index)
-                        .collect();
-                    indices.extend_from_slice(&adjusted_indices);
-                }
+                // Randomly determine which block to use
+                let random_chance: f32 = rng.gen(); // Generate a random float between 0.0 and 1.0
+                let block = if random_chance < 0.08 {
+                    // 8% chance to use the secondary block
+                    lower_block
+                } else {
+                    upper_block
+                };
+
+                voxels.set(x, y, z, block);
             }
         }
     }
 }
 
+// Angle along the spiral of a world column
+fn spiral_angle(world_x: f64, world_z: f64, scale: f64, spiral_factor: f64, num_rings: f64) -> f64 {
+    // Convert to polar coordinates to create a spiral effect
+    let radius = (world_x.powi(2) + world_z.powi(2)).sqrt() * scale;
+
+    // Introduce the num_rings factor to control how many spirals occur
+    world_x.atan2(world_z) + (spiral_factor * radius) / num_rings
+}
+
+// Height the spiral ridge starts at in a world column
+pub fn spiral_height(
+    flat_height: i32,
+    scale: f64,
+    height_scale: f64,
+    spiral_factor: f64,
+    num_rings: f64,
+    world_x: i32,
+    world_z: i32,
+) -> i32 {
+    let angle = spiral_angle(world_x as f64, world_z as f64, scale, spiral_factor, num_rings);
+
+    // Control the height using a sinusoidal function based on the angle (creates waves)
+    (angle.sin() * height_scale).round() as i32 + flat_height
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_spiral_mountain_terrain(
     chunk_position: Point3<i32>,
     flat_height: i32,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
+    voxels: &mut ChunkVoxels,
     mountain_width: i32,
     scale: f64,
     height_scale: f64,
-    extended_size: i32,
-    texture_id_1: u32,  // First texture ID
-    texture_id_2: u32,  // Second texture ID
+    block_1: Block,     // First block
+    block_2: Block,     // Second block
     spiral_factor: f64, // Control how tight the spiral is
     num_rings: f64      // Control the number of spiral rings
 ) {
-    let mut height_map = vec![vec![0; extended_size as usize]; extended_size as usize];
-
-    // First loop: generate height map using Perlin noise and spiral calculation
-    for x in 0..extended_size {
-        for z in 0..extended_size {
-            let world_x = (chunk_position.x * CHUNK_SIZE + x - OVERLAP) as f64;
-            let world_z = (chunk_position.z * CHUNK_SIZE + z - OVERLAP) as f64;
-
-            // Convert to polar coordinates to create a spiral effect
-            let radius = (world_x.powi(2) + world_z.powi(2)).sqrt() * scale;
+    let height_field = HeightField::new(|world_x, world_z| {
+        spiral_height(flat_height, scale, height_scale, spiral_factor, num_rings, world_x, world_z)
+    });
 
-            // Introduce the num_rings factor to control how many spirals occur
-            let angle = world_x.atan2(world_z) + (spiral_factor * radius) / num_rings;
-
-            // Control the height using a sinusoidal function based on the angle (creates waves)
-            let height = (angle.sin() * height_scale).round() as i32 + flat_height;
-
-            height_map[x as usize][z as usize] = height;
-        }
-    }
-
-    // Second loop: generate cubes using the height map
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
-            let base_height = height_map[(x + OVERLAP) as usize][(z + OVERLAP) as usize];
-
-            for dy in 0..mountain_width {
-                let height = base_height + dy;
-
-                if height > flat_height {
-                    for y in (flat_height + 1)..=height {
-                        if is_block_exposed(x, y, z, &height_map) {
-                            let offset = Vector3::new(x as f32, y as f32, z as f32);
-
-                            // Recalculate world_x, world_z, radius, and angle
-                            let world_x = (chunk_position.x * CHUNK_SIZE + x - OVERLAP) as f64;
-                            let world_z = (chunk_position.z * CHUNK_SIZE + z - OVERLAP) as f64;
-                            let radius = (world_x.powi(2) + world_z.powi(2)).sqrt() * scale;
-                            let angle = world_x.atan2(world_z) + (spiral_factor * radius) / num_rings;
-
-                            // Alternate textures based on the angle of the spiral
-                            let texture_id = if angle.sin() > 0.0 {
-                                texture_id_1  // Use first texture for positive angles
-                            } else {
-                                texture_id_2  // Use second texture for negative angles
-                            };
-
-                            let (cube_vertices, cube_indices) = create_cube(offset, texture_id);
-
-                            let base_index = vertices.len() as u32;
-                            vertices.extend_from_slice(&cube_vertices);
-
-                            let cube_indices: Vec<u32> = cube_indices.iter()
-                                .map(|&idx| idx as u32 + base_index)
-                                .collect();
-                            indices.extend_from_slice(&cube_indices);
-                        }
-                    }
-                }
+            let (world_x, world_z) = column_world_position(chunk_position, x, z);
+            let base_height = height_field.sample(world_x, world_z);
+            let top = base_height + mountain_width - 1;
+
+            // Alternate blocks based on the angle of the spiral
+            let angle = spiral_angle(world_x as f64, world_z as f64, scale, spiral_factor, num_rings);
+            let block = if angle.sin() > 0.0 {
+                block_1  // Use first block for positive angles
+            } else {
+                block_2  // Use second block for negative angles
+            };
+
+            for y in (flat_height + 1)..=top {
+                voxels.set(x, y, z, block);
             }
         }
     }
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_trees(
     chunk_position: Point3<i32>,
     flat_height: i32,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
-    perlin: &Perlin,
+    voxels: &mut ChunkVoxels,
+    noise: &NoiseLayer,
     tree_density: f64,
     tree_height: i32,
-    trunk_texture_id: u32,
-    leaf_texture_id: u32
+    trunk_block: Block,
+    leaf_block: Block
 ) {
-    let tree_scale = 0.05; // Adjust this to change the distribution of trees
+    // Trees standing just past the border still hang their leaves into this chunk
+    let reach = TREE_LEAF_RADIUS as i32;
 
-    for x in 0..CHUNK_SIZE {
-        for z in 0..CHUNK_SIZE {
-            let world_x = (chunk_position.x * CHUNK_SIZE + x) as f64;
-            let world_z = (chunk_position.z * CHUNK_SIZE + z) as f64;
+    for x in -reach..CHUNK_SIZE + reach {
+        for z in -reach..CHUNK_SIZE + reach {
+            let (world_x, world_z) = column_world_position(chunk_position, x, z);
 
-            // Use Perlin noise to determine if a tree should be placed
-            let noise_value = perlin.get([world_x * tree_scale, world_z * tree_scale]);
+            // Use the noise layer to determine if a tree should be placed
+            let noise_value = noise.get(world_x as f64, world_z as f64);
 
             if noise_value > 1.0 - tree_density {
                 // Use flat_height as the base height for all trees
                 let base_height = flat_height;
 
                 // We don't need to check if the location is suitable since it's flat terrain
-                generate_tree(
-                    x as f32,
-                    base_height as f32,
-                    z as f32,
-                    tree_height,
-                    vertices,
-                    indices,
-                    trunk_texture_id,
-                    leaf_texture_id
-                );
+                generate_tree(x, base_height, z, tree_height, voxels, trunk_block, leaf_block);
             }
         }
     }
 }
 
+// Radius of the spherical canopy
+const TREE_LEAF_RADIUS: f32 = 3.0;
+
 fn generate_tree(
-    x: f32,
-    y: f32,
-    z: f32,
+    x: i32,
+    y: i32,
+    z: i32,
     height: i32,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
-    trunk_texture_id: u32,
-    leaf_texture_id: u32
+    voxels: &mut ChunkVoxels,
+    trunk_block: Block,
+    leaf_block: Block
 ) {
     // Generate trunk
     for i in 0..height {
-        let offset = Vector3::new(x, y + i as f32, z);
-
-        let (cube_vertices, cube_indices) = create_cube(offset, trunk_texture_id);
-
-        let base_index = vertices.len() as u32;
-        vertices.extend_from_slice(&cube_vertices);
-        let cube_indices: Vec<u32> = cube_indices.iter()
-            .map(|&idx| idx as u32 + base_index)
-            .collect();
-        indices.extend_from_slice(&cube_indices);
+        voxels.set(x, y + i, z, trunk_block);
     }
 
-    // Generate spherical leaves
-    let leaf_center = Vector3::new(x, y + height as f32, z);
-    let leaf_radius = 3.0;  // Set the radius for the spherical canopy
+    // Generate spherical leaves around the top of the trunk
+    let leaf_radius = TREE_LEAF_RADIUS as i32;
 
     // Loop over a cube that encompasses the leaf sphere
-    for dx in -leaf_radius as i32..=leaf_radius as i32 {
-        for dy in -leaf_radius as i32..=leaf_radius as i32 {
-            for dz in -leaf_radius as i32..=leaf_radius as i32 {
-                // Calculate the position of the current leaf cube
-                let leaf_pos = leaf_center + Vector3::new(dx as f32, dy as f32, dz as f32);
-
+    for dx in -leaf_radius..=leaf_radius {
+        for dy in -leaf_radius..=leaf_radius {
+            for dz in -leaf_radius..=leaf_radius {
                 // Calculate the distance from the leaf center
                 let distance = ((dx * dx + dy * dy + dz * dz) as f32).sqrt();
 
-                // Only place leaf cubes if they are within the radius of the sphere
-                if distance <= leaf_radius {
-                    let (leaf_vertices, leaf_indices) = create_cube(leaf_pos, leaf_texture_id);
-                    let base_index = vertices.len() as u32;
-                    vertices.extend_from_slice(&leaf_vertices);
-                    let leaf_indices: Vec<u32> = leaf_indices.iter()
-                        .map(|&idx| idx as u32 + base_index)
-                        .collect();
-                    indices.extend_from_slice(&leaf_indices);
+                // Only place leaves within the radius of the sphere, without replacing the trunk
+                if distance <= TREE_LEAF_RADIUS {
+                    voxels.set_if_air(x + dx, y + height + dy, z + dz, leaf_block);
                 }
             }
         }
@@ -262,127 +211,224 @@ fn generate_tree(
 }
 
 
-fn is_block_exposed(x: i32, y: i32, z: i32, height_map: &Vec<Vec<i32>>) -> bool {
-    let check_positions = [
-        (x, z), // Current position
-        (x, z - 1), (x, z + 1), // North, South
-        (x - 1, z), (x + 1, z), // West, East
-    ];
-
-    let map_size = height_map.len() as i32;
-
-    for (nx, nz) in check_positions.iter() {
-        let hm_x = (nx + OVERLAP).clamp(0, map_size - 1) as usize;
-        let hm_z = (nz + OVERLAP).clamp(0, map_size - 1) as usize;
-
-        // Check if the block is exposed from the top or sides
-        if y > height_map[hm_x][hm_z] {
-            return true;
-        }
-    }
+// Number of blocks the hills stack up in a world column
+pub fn hills_height(noise: &NoiseLayer, height_scale: f64, base_height: i32, world_x: i32, world_z: i32) -> i32 {
+    // Get noise value for terrain height at this (x, z)
+    let noise_value = noise.get(world_x as f64, world_z as f64);
 
-    false
+    // Map noise value (-1.0 to 1.0) to a terrain height (e.g., 0 to 30 blocks)
+    ((noise_value + 1.0) / 2.0 * height_scale) as i32 + base_height
 }
 
 pub fn generate_terrain_chunk(
     chunk_position: Point3<i32>,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
-    perlin: &Perlin,
-    scale: f64,
+    voxels: &mut ChunkVoxels,
+    noise: &NoiseLayer,
     height_scale: f64,
     base_height: i32,
-    texture_id: u32,
+    block: Block,
 ) {
+    let height_field = HeightField::new(|world_x, world_z| hills_height(noise, height_scale, base_height, world_x, world_z));
+
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
-            let world_x = (chunk_position.x * CHUNK_SIZE + x) as f64;
-            let world_z = (chunk_position.z * CHUNK_SIZE + z) as f64;
-
-            // Get noise value for terrain height at this (x, z)
-            let noise_value = perlin.get([world_x * scale, world_z * scale]);
-            
-            // Map noise value (-1.0 to 1.0) to a terrain height (e.g., 0 to 30 blocks)
-            let terrain_height = ((noise_value + 1.0) / 2.0 * height_scale) as i32 + base_height;
+            let (world_x, world_z) = column_world_position(chunk_position, x, z);
+            let terrain_height = height_field.sample(world_x, world_z);
 
             // Generate blocks for terrain
             for y in 0..terrain_height {
-                let offset = Vector3::new(x as f32, y as f32, z as f32);
-                let (cube_vertices, cube_indices) = create_cube(offset, texture_id);
-                let base_index = vertices.len() as u32;
-                vertices.extend_from_slice(&cube_vertices);
-                let cube_indices: Vec<u32> = cube_indices.iter()
-                    .map(|&idx| idx as u32 + base_index)
-                    .collect();
-                indices.extend_from_slice(&cube_indices);
+                voxels.set(x, y, z, block);
             }
         }
     }
 }
 
+// Height the arches start at in a world column
+pub fn arch_height(flat_height: i32, height_scale: f64, arch_factor: f64, num_arches: i32, world_x: i32) -> i32 {
+    // The arches repeat every chunk along x
+    let local_x = world_x.rem_euclid(CHUNK_SIZE);
+
+    // Control the height using a cosine function to create arch-like peaks
+    let arch_position = ((local_x as f64 / CHUNK_SIZE as f64) * num_arches as f64).floor();
+    (arch_position * arch_factor * (PI * 2.0) + (world_x as f64).cos() * height_scale).round() as i32 + flat_height
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_arch_mountain_terrain(
     chunk_position: Point3<i32>,
     flat_height: i32,
-    vertices: &mut Vec<Vertex>,
-    indices: &mut Vec<u32>,
+    voxels: &mut ChunkVoxels,
     mountain_width: i32,
-    scale: f64,  
     height_scale: f64,
-    extended_size: i32,
-    texture_id_1: u32,  // First texture ID
-    texture_id_2: u32,  // Second texture ID
-    arch_factor: f64,    // Control the arch height and width
-    num_arches: i32      // Control the number of arches
+    block_1: Block,     // First block
+    block_2: Block,     // Second block
+    arch_factor: f64,   // Control the arch height and width
+    num_arches: i32     // Control the number of arches
 ) {
-    let mut height_map = vec![vec![0; extended_size as usize]; extended_size as usize];
+    let height_field = HeightField::new(|world_x, _world_z| {
+        arch_height(flat_height, height_scale, arch_factor, num_arches, world_x)
+    });
 
-    // First loop: generate height map with arches
-    for x in 0..extended_size {
-        for z in 0..extended_size {
-            let world_x = (chunk_position.x * CHUNK_SIZE + x - OVERLAP) as f64;
-            let world_z = (chunk_position.z * CHUNK_SIZE + z - OVERLAP) as f64;
-
-            // Control the height using a cosine function to create arch-like peaks
-            let arch_position = ((x as f64 / extended_size as f64) * num_arches as f64).floor();
-            let height = (arch_position * arch_factor * (PI * 2.0) + world_x.cos() * height_scale).round() as i32 + flat_height;
-
-            height_map[x as usize][z as usize] = height;
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let (world_x, world_z) = column_world_position(chunk_position, x, z);
+            let base_height = height_field.sample(world_x, world_z);
+            let top = base_height + mountain_width - 1;
+
+            for y in (flat_height + 1)..=top {
+                // Alternate blocks based on the arch position
+                let block = if (y - base_height) % 2 == 0 {
+                    block_1  // Use first block for one side of the arch
+                } else {
+                    block_2  // Use second block for the other side
+                };
+
+                voxels.set(x, y, z, block);
+            }
         }
     }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn generate_floating_islands(
+    chunk_position: Point3<i32>,
+    ground_height: i32,
+    voxels: &mut ChunkVoxels,
+    coverage: &NoiseLayer,  // 2d mask deciding where islands float
+    shape: &NoiseLayer,     // 3d mask carving overhangs into the islands
+    altitude: i32,          // Height the island tops float at
+    depth: i32,             // How far the undersides hang below the tops
+    surface_block: Block,
+    underside_block: Block,
+    waterfall_block: Block,
+) {
+    let island_grid = IslandGrid::new(chunk_position, coverage, shape, altitude, depth);
 
-    // Second loop: generate cubes using the height map
     for x in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
-            let base_height = height_map[(x + OVERLAP) as usize][(z + OVERLAP) as usize];
+            for y in island_grid.min_y..island_grid.max_y {
+                if !island_grid.is_solid(x, y, z) {
+                    continue;
+                }
+
+                // Only the blocks with open sky above them get the surface block
+                let block = if island_grid.is_solid(x, y + 1, z) {
+                    underside_block
+                } else {
+                    surface_block
+                };
 
-            for dy in 0..mountain_width {
-                let height = base_height + dy;
+                voxels.set(x, y, z, block);
+            }
 
-                if height > flat_height {
-                    for y in (flat_height + 1)..=height {
-                        if is_block_exposed(x, y, z, &height_map) {
-                            let offset = Vector3::new(x as f32, y as f32, z as f32);
+            // Water spills from springs on the island edge into this column if it is open air next to them
+            let Some(spring_height) = island_grid.waterfall_source(chunk_position, x, z) else {
+                continue;
+            };
 
-                            // Alternate textures based on the arch position
-                            let texture_id = if (y - base_height) % 2 == 0 {
-                                texture_id_1  // Use first texture for one side of the arch
-                            } else {
-                                texture_id_2  // Use second texture for the other side
-                            };
+            for y in ((ground_height + 1)..=spring_height).rev() {
+                if island_grid.is_solid(x, y, z) {
+                    break;
+                }
 
-                            let (cube_vertices, cube_indices) = create_cube(offset, texture_id);
+                voxels.set_if_air(x, y, z, waterfall_block);
+            }
+        }
+    }
+}
+
+// Islands only form where the coverage mask is above this, and are at their thickest past the full value
+const ISLAND_COVERAGE_THRESHOLD: f64 = 0.2;
+const ISLAND_COVERAGE_FULL: f64 = 0.4;
+// Roughly one in this many edge columns is a waterfall spring
+const WATERFALL_RARITY: u32 = 12;
+
+// Solid/air samples of the island band for a chunk plus one column of each neighbour,
+// sampled once up front since the 3d noise is the expensive part
+struct IslandGrid {
+    solid: Vec<bool>,
+    min_y: i32,
+    max_y: i32,
+}
 
-                            let base_index = vertices.len() as u32;
-                            vertices.extend_from_slice(&cube_vertices);
+impl IslandGrid {
+    const SIZE: i32 = CHUNK_SIZE + 2;
 
-                            let cube_indices: Vec<u32> = cube_indices.iter()
-                                .map(|&idx| idx as u32 + base_index)
-                                .collect();
-                            indices.extend_from_slice(&cube_indices);
-                        }
+    fn new(chunk_position: Point3<i32>, coverage: &NoiseLayer, shape: &NoiseLayer, altitude: i32, depth: i32) -> Self {
+        let top_height = 6; // How high the island surfaces bulge above the altitude
+        let carve = 4.0; // How far the 3d noise can push the surfaces up and down
+
+        let min_y = altitude - depth - carve as i32;
+        let max_y = altitude + top_height + carve as i32 + 1;
+        let height = max_y - min_y;
+        let mut solid = vec![false; (Self::SIZE * Self::SIZE * height) as usize];
+
+        for x in -1..=CHUNK_SIZE {
+            for z in -1..=CHUNK_SIZE {
+                let (world_x, world_z) = column_world_position(chunk_position, x, z);
+
+                // Normalised distance into the island, 0 at the edge and 1 in the middle
+                let edge = ((coverage.get(world_x as f64, world_z as f64) - ISLAND_COVERAGE_THRESHOLD)
+                    / (ISLAND_COVERAGE_FULL - ISLAND_COVERAGE_THRESHOLD)).min(1.0);
+                if edge <= 0.0 {
+                    continue;
+                }
+
+                // Flat-ish tops and undersides tapering down towards the middle
+                let top = altitude as f64 + edge.sqrt() * top_height as f64;
+                let bottom = altitude as f64 - edge * depth as f64;
+
+                for y in min_y..max_y {
+                    let offset = shape.get3(world_x as f64, y as f64, world_z as f64) * carve;
+                    let carved_y = y as f64 + offset;
+                    if carved_y <= top && carved_y >= bottom {
+                        solid[Self::index(x, y - min_y, z, height)] = true;
                     }
                 }
             }
         }
+
+        IslandGrid { solid, min_y, max_y }
+    }
+
+    fn index(x: i32, y: i32, z: i32, height: i32) -> usize {
+        (((x + 1) * Self::SIZE + (z + 1)) * height + y) as usize
     }
+
+    // Chunk local (x, z) from -1 to CHUNK_SIZE, anything outside the band is air
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        if y < self.min_y || y >= self.max_y {
+            return false;
+        }
+        self.solid[Self::index(x, y - self.min_y, z, self.max_y - self.min_y)]
+    }
+
+    fn surface_height(&self, x: i32, z: i32) -> Option<i32> {
+        (self.min_y..self.max_y).rev().find(|&y| self.is_solid(x, y, z))
+    }
+
+    // Height water starts falling from in this column, if an island spring overhangs it
+    fn waterfall_source(&self, chunk_position: Point3<i32>, x: i32, z: i32) -> Option<i32> {
+        let neighbours = [(x + 1, z), (x - 1, z), (x, z + 1), (x, z - 1)];
+
+        neighbours.iter().find_map(|&(nx, nz)| {
+            let (world_x, world_z) = column_world_position(chunk_position, nx, nz);
+            if !column_hash(world_x, world_z).is_multiple_of(WATERFALL_RARITY) {
+                return None;
+            }
+
+            // The spring's surface has to be open to this column for water to spill over
+            let spring_height = self.surface_height(nx, nz)?;
+            (!self.is_solid(x, spring_height, z)).then_some(spring_height)
+        })
+    }
+}
+
+// Cheap deterministic hash of a world column
+fn column_hash(world_x: i32, world_z: i32) -> u32 {
+    let mut hash = (world_x as u32).wrapping_mul(0x27d4_eb2d) ^ (world_z as u32).wrapping_mul(0x1656_67b1);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash
 }
@@ -0,0 +1,110 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::constants::world::CHUNK_SIZE;
+
+use super::coords::ChunkPos;
+use super::mesher::FACE_DIRECTIONS;
+use super::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+// Which faces of a chunk can see each other through its see-through blocks.
+// Faces are numbered like `FACE_DIRECTIONS`, so a face's opposite is its number with the last bit flipped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkVisibility {
+    // Bit `from * 6 + to` is set when the two faces are connected
+    connections: u64,
+}
+
+impl ChunkVisibility {
+    // Every face sees every other face, like a chunk full of air
+    pub fn open() -> Self {
+        ChunkVisibility {
+            connections: (1 << 36) - 1,
+        }
+    }
+
+    // Flood fill the chunk's see-through blocks, connecting every pair of faces a pocket of them touches
+    pub fn compute(voxels: &ChunkVoxels) -> Self {
+        let index = |x: i32, y: i32, z: i32| ((x * CHUNK_SIZE + z) * CHUNK_HEIGHT + y) as usize;
+        let mut visited = vec![false; (CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE) as usize];
+        let mut visibility = ChunkVisibility::default();
+        let mut stack = Vec::new();
+
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_HEIGHT {
+                    if visited[index(x, y, z)] || voxels.get(x, y, z).is_opaque() {
+                        continue;
+                    }
+
+                    // Faces this pocket reaches, one bit per face
+                    let mut faces = 0u8;
+                    visited[index(x, y, z)] = true;
+                    stack.push((x, y, z));
+                    while let Some((x, y, z)) = stack.pop() {
+                        for (face, (dx, dy, dz)) in FACE_DIRECTIONS.iter().enumerate() {
+                            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                            if !ChunkVoxels::in_bounds(nx, ny, nz) {
+                                faces |= 1 << face;
+                                continue;
+                            }
+                            if !visited[index(nx, ny, nz)] && !voxels.get(nx, ny, nz).is_opaque() {
+                                visited[index(nx, ny, nz)] = true;
+                                stack.push((nx, ny, nz));
+                            }
+                        }
+                    }
+
+                    for from in 0..6 {
+                        for to in 0..6 {
+                            if faces & (1 << from) != 0 && faces & (1 << to) != 0 {
+                                visibility.connections |= 1 << (from * 6 + to);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        visibility
+    }
+
+    pub fn connects(&self, from: usize, to: usize) -> bool {
+        self.connections & (1 << (from * 6 + to)) != 0
+    }
+}
+
+// Chunks that could be seen from the camera chunk, found by walking from chunk to chunk through
+// connected faces. The walk never turns back against a direction it already went in, so it can't
+// look around corners. `None` if the camera chunk isn't loaded and nothing can be ruled out.
+pub fn visible_chunks(camera_chunk: ChunkPos, visibility: impl Fn(ChunkPos) -> Option<ChunkVisibility>) -> Option<HashSet<ChunkPos>> {
+    visibility(camera_chunk)?;
+
+    // Every chunk is reached once, remembering the face the walk came in through and the directions it went in
+    let mut visited = HashSet::from([camera_chunk]);
+    let mut queue = VecDeque::from([(camera_chunk, None::<usize>, 0u8)]);
+
+    while let Some((position, entered_through, directions)) = queue.pop_front() {
+        let Some(chunk_visibility) = visibility(position) else {
+            continue;
+        };
+
+        for (face, (dx, dy, dz)) in FACE_DIRECTIONS.iter().enumerate() {
+            // Going back the way the walk came can only reach chunks hidden behind the ones already seen
+            if directions & (1 << (face ^ 1)) != 0 {
+                continue;
+            }
+            if entered_through.is_some_and(|entered| !chunk_visibility.connects(entered, face)) {
+                continue;
+            }
+
+            let neighbour = position.offset(*dx, *dy, *dz);
+            if visited.contains(&neighbour) || visibility(neighbour).is_none() {
+                continue;
+            }
+            visited.insert(neighbour);
+            queue.push_back((neighbour, Some(face ^ 1), directions | (1 << face)));
+        }
+    }
+
+    Some(visited)
+}
@@ -0,0 +1,66 @@
+use crate::constants::world::CHUNK_SIZE;
+
+use super::block::Block;
+
+// Number of blocks a chunk column holds, from y = 0 up
+pub const CHUNK_HEIGHT: i32 = 256;
+
+// Blocks of one chunk column in chunk local coordinates
+#[derive(Clone)]
+pub struct ChunkVoxels {
+    blocks: Vec<Block>,
+}
+
+impl Default for ChunkVoxels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkVoxels {
+    pub fn new() -> Self {
+        ChunkVoxels {
+            blocks: vec![Block::Air; (CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE) as usize],
+        }
+    }
+
+    pub fn in_bounds(x: i32, y: i32, z: i32) -> bool {
+        (0..CHUNK_SIZE).contains(&x) && (0..CHUNK_HEIGHT).contains(&y) && (0..CHUNK_SIZE).contains(&z)
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        ((x * CHUNK_SIZE + z) * CHUNK_HEIGHT + y) as usize
+    }
+
+    // Anything outside the chunk reads as air
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Block {
+        if !Self::in_bounds(x, y, z) {
+            return Block::Air;
+        }
+        self.blocks[Self::index(x, y, z)]
+    }
+
+    // Writes outside the chunk are dropped, generators can place blocks without clipping themselves
+    pub fn set(&mut self, x: i32, y: i32, z: i32, block: Block) {
+        if Self::in_bounds(x, y, z) {
+            self.blocks[Self::index(x, y, z)] = block;
+        }
+    }
+
+    // Only place the block if nothing is there yet
+    pub fn set_if_air(&mut self, x: i32, y: i32, z: i32, block: Block) {
+        if self.get(x, y, z).is_air() {
+            self.set(x, y, z, block);
+        }
+    }
+
+    // Bytes the blocks take up
+    pub fn memory_size(&self) -> usize {
+        self.blocks.len() * std::mem::size_of::<Block>()
+    }
+
+    // Highest non air block of a column
+    pub fn surface_height(&self, x: i32, z: i32) -> Option<i32> {
+        (0..CHUNK_HEIGHT).rev().find(|&y| !self.get(x, y, z).is_air())
+    }
+}
@@ -0,0 +1,263 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use super::border::BorderBlocks;
+use super::chunk::{generate_chunk, GeneratedChunk};
+use super::coords::ChunkPos;
+use super::generator::GeneratorConfig;
+use super::light::{BorderLight, ChunkLight};
+use super::mesher::mesh_chunk_with_borders;
+use super::voxels::ChunkVoxels;
+
+enum ChunkWork {
+    // Generate, light and mesh a new chunk
+    Generate,
+    // Mesh a chunk whose blocks are already known, lighting it first if its light isn't
+    Mesh(ChunkVoxels, Option<ChunkLight>),
+    // Mesh a loaded chunk again, after its light or the blocks or light next to it changed
+    Remesh(ChunkVoxels, ChunkLight, BorderBlocks, BorderLight),
+}
+
+impl ChunkWork {
+    fn stopped(self) -> WorkersStopped {
+        match self {
+            ChunkWork::Generate => WorkersStopped { voxels: None, light: None },
+            ChunkWork::Mesh(voxels, light) => WorkersStopped { voxels: Some(voxels), light },
+            ChunkWork::Remesh(voxels, light, ..) => WorkersStopped { voxels: Some(voxels), light: Some(light) },
+        }
+    }
+}
+
+struct ChunkJob {
+    id: u64,
+    position: ChunkPos,
+    work: ChunkWork,
+    cancelled: Arc<AtomicBool>,
+}
+
+// Handle to a submitted job, cancelling it makes the worker stop at its next check
+#[derive(Clone)]
+pub struct ChunkJobHandle {
+    pub id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ChunkJobHandle {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+// A job the workers are done with, `chunk` is `None` if it was cancelled before it finished or it panicked
+pub struct FinishedJob {
+    pub id: u64,
+    pub position: ChunkPos,
+    pub chunk: Option<GeneratedChunk>,
+    // Submitted with `submit_remesh`, only the chunk's mesh is new
+    pub remesh: bool,
+}
+
+// Counts of what happened to the chunk jobs, for seeing how much work is thrown away
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkJobMetrics {
+    // Jobs handed to the workers
+    pub submitted: usize,
+    // Generated chunks that made it into the world
+    pub uploaded: usize,
+    // Queued chunks dropped before a worker ever saw them
+    pub dropped_from_queue: usize,
+    // Jobs cancelled while the workers had them
+    pub cancelled_in_flight: usize,
+    // Cancelled jobs the workers stopped before finishing, and jobs that panicked
+    pub abandoned: usize,
+    // Jobs that finished anyway and whose chunk was thrown away
+    pub discarded: usize,
+    // Meshes of loaded chunks rebuilt after their light changed
+    pub remeshed: usize,
+}
+
+// Every worker is gone, so nothing will pick a job up. Hands back the blocks and light the job came with.
+pub struct WorkersStopped {
+    pub voxels: Option<ChunkVoxels>,
+    pub light: Option<ChunkLight>,
+}
+
+impl fmt::Debug for WorkersStopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkersStopped").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for WorkersStopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chunk workers stopped")
+    }
+}
+
+impl std::error::Error for WorkersStopped {}
+
+// Pool of threads generating and meshing chunks off the render thread.
+// Jobs are picked up in the order they are submitted, finished chunks come back over a channel.
+pub struct ChunkWorkers {
+    jobs: Option<Sender<ChunkJob>>,
+    job_receiver: Arc<Mutex<Receiver<ChunkJob>>>,
+    finished_sender: Sender<FinishedJob>,
+    finished: Receiver<FinishedJob>,
+    generator: Arc<GeneratorConfig>,
+    threads: Vec<JoinHandle<()>>,
+    next_job_id: u64,
+}
+
+impl ChunkWorkers {
+    // A pool without threads holds on to its jobs until some are added
+    pub fn new(generator: GeneratorConfig, thread_count: usize) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<ChunkJob>();
+        let (finished_sender, finished) = mpsc::channel();
+
+        let mut workers = ChunkWorkers {
+            jobs: Some(job_sender),
+            job_receiver: Arc::new(Mutex::new(job_receiver)),
+            finished_sender,
+            finished,
+            generator: Arc::new(generator),
+            threads: Vec::new(),
+            next_job_id: 0,
+        };
+        workers.add_threads(thread_count);
+        workers
+    }
+
+    // Start more workers, they pick up the jobs waiting for them first
+    pub fn add_threads(&mut self, count: usize) {
+        for _ in 0..count {
+            let job_receiver = Arc::clone(&self.job_receiver);
+            let finished_sender = self.finished_sender.clone();
+            let generator = Arc::clone(&self.generator);
+
+            let thread = thread::Builder::new()
+                .name(format!("chunk-worker-{}", self.threads.len()))
+                .spawn(move || loop {
+                    // Only hold the lock while waiting for a job, not while working on it
+                    let job = job_receiver.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    let Ok(job) = job else {
+                        break; // The pool was dropped
+                    };
+
+                    // A job that panics comes back abandoned, the worker carries on with the next one
+                    let (id, position, remesh) = (job.id, job.position, matches!(job.work, ChunkWork::Remesh(..)));
+                    let chunk = panic::catch_unwind(AssertUnwindSafe(|| run_job(job, &generator))).unwrap_or(None);
+                    let finished = FinishedJob { id, position, chunk, remesh };
+                    if finished_sender.send(finished).is_err() {
+                        break;
+                    }
+                })
+                .expect("failed to spawn chunk worker");
+            self.threads.push(thread);
+        }
+    }
+
+    // One worker per core, leaving a core for the render thread
+    pub fn default_thread_count() -> usize {
+        thread::available_parallelism().map_or(1, |cores| cores.get().saturating_sub(1).max(1))
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    // Generate and mesh a chunk
+    pub fn submit(&mut self, position: ChunkPos) -> Result<ChunkJobHandle, WorkersStopped> {
+        self.send(position, ChunkWork::Generate)
+    }
+
+    // Only mesh a chunk whose blocks are already known, and its light if that's known too
+    pub fn submit_mesh(&mut self, position: ChunkPos, voxels: ChunkVoxels, light: Option<ChunkLight>) -> Result<ChunkJobHandle, WorkersStopped> {
+        self.send(position, ChunkWork::Mesh(voxels, light))
+    }
+
+    // Mesh a copy of a loaded chunk again with the blocks and light of the chunks next to it
+    pub fn submit_remesh(
+        &mut self,
+        position: ChunkPos,
+        voxels: ChunkVoxels,
+        light: ChunkLight,
+        border_blocks: BorderBlocks,
+        border_light: BorderLight,
+    ) -> Result<ChunkJobHandle, WorkersStopped> {
+        self.send(position, ChunkWork::Remesh(voxels, light, border_blocks, border_light))
+    }
+
+    fn send(&mut self, position: ChunkPos, work: ChunkWork) -> Result<ChunkJobHandle, WorkersStopped> {
+        let handle = ChunkJobHandle {
+            id: self.next_job_id,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        self.next_job_id += 1;
+
+        let Some(jobs) = &self.jobs else {
+            return Err(work.stopped());
+        };
+        let job = ChunkJob {
+            id: handle.id,
+            position,
+            work,
+            cancelled: Arc::clone(&handle.cancelled),
+        };
+        match jobs.send(job) {
+            Ok(()) => Ok(handle),
+            Err(mpsc::SendError(job)) => Err(job.work.stopped()),
+        }
+    }
+
+    // Jobs finished since the last call, never blocks
+    pub fn finished(&self) -> impl Iterator<Item = FinishedJob> + '_ {
+        self.finished.try_iter()
+    }
+}
+
+// Generate and mesh a chunk, checking for cancellation before each of the expensive steps.
+// Chunks that came with their blocks are always meshed, so their blocks aren't lost with the job.
+// Remeshed chunks still have their blocks in the world, so those can stop.
+fn run_job(job: ChunkJob, generator: &GeneratorConfig) -> Option<GeneratedChunk> {
+    let is_cancelled = || job.cancelled.load(Ordering::Relaxed);
+
+    match job.work {
+        ChunkWork::Generate => {
+            if is_cancelled() {
+                return None;
+            }
+            let voxels = generate_chunk(job.position.into(), generator);
+            if is_cancelled() {
+                return None;
+            }
+            Some(GeneratedChunk::from_voxels(job.position, voxels))
+        }
+        ChunkWork::Mesh(voxels, None) => Some(GeneratedChunk::from_voxels(job.position, voxels)),
+        ChunkWork::Mesh(voxels, Some(light)) => Some(GeneratedChunk::with_light(job.position, voxels, light)),
+        ChunkWork::Remesh(voxels, light, border_blocks, border_light) => {
+            if is_cancelled() {
+                return None;
+            }
+            let chunk_data = mesh_chunk_with_borders(&voxels, &light, &border_blocks, &border_light);
+            Some(GeneratedChunk { position: job.position, voxels, light, chunk_data })
+        }
+    }
+}
+
+impl Drop for ChunkWorkers {
+    fn drop(&mut self) {
+        // Closing the job channel lets every worker run out of work and stop
+        self.jobs.take();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
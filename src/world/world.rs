@@ -1,148 +1,431 @@
-use std::collections::HashSet;
-use std::sync::{mpsc, Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
-use cgmath::{Matrix4, Point3, Vector3};
-use glium::{glutin::surface::WindowSurface, uniform, uniforms::Sampler, Surface};
+use cgmath::{Point3, Vector3};
+use glium::glutin::surface::WindowSurface;
 
-use crate::{camera::camera::Camera, graphics::cube::FaceUVs};
+use crate::constants::world::CHUNK_SIZE;
 
-use super::chunk::Chunk;
+use super::block::Block;
+use super::border::BorderBlocks;
+use super::chunk::{Chunk, ChunkData, GeneratedChunk};
+use super::chunk_cache::{CachedChunk, ChunkCache};
+use super::clock::WorldClock;
+use super::coords::{BlockPos, ChunkPos, LocalPos};
+use super::generator::GeneratorConfig;
+use super::light::{relight_block, spread_between_chunks, BorderLight, ChunkLight, Light, LightWorld};
+use super::load_queue::ChunkLoadQueue;
+use super::mesher::mesh_chunk_with_borders;
+use super::preset::WorldPreset;
+use super::render_distance::{chunk_distance_squared, RenderDistance};
+use super::voxels::ChunkVoxels;
+use super::visibility::visible_chunks;
+use super::workers::{ChunkJobHandle, ChunkJobMetrics, ChunkWorkers, FinishedJob};
+
+// Seed every world is currently generated with
+const WORLD_SEED: u32 = 142;
+// Bytes of unloaded chunks kept around in case the camera comes back
+const CHUNK_CACHE_CAPACITY: usize = 64 * 1024 * 1024;
 
 pub struct World {
-    pub chunks: Vec<Chunk>,
-    pub chunk_positions: HashSet<Point3<i32>>,
-    pub chunk_size: i32,
-    pub last_camera_chunk_position: Point3<i32>, // Track the last chunk position
-    pub chunk_radius: i32,  
-    pub chunk_generation_queue: Vec<Point3<i32>>,
+    pub chunks: HashMap<ChunkPos, Chunk>,
+    pub last_camera_chunk_position: ChunkPos, // Track the last chunk position
+    pub render_distance: RenderDistance,
+    // Time each frame may spend on loading and unloading chunks
+    pub frame_budget: Duration,
+    pub chunk_generation_queue: ChunkLoadQueue,
+    // Chunks handed to the workers that haven't come back yet
+    pub chunks_in_flight: HashMap<ChunkPos, ChunkJobHandle>,
+    pub job_metrics: ChunkJobMetrics,
+    // Recently unloaded chunks, loaded back without generating them again
+    pub chunk_cache: ChunkCache,
+    pub preset: WorldPreset,
+    pub generator: GeneratorConfig,
+    pub clock: WorldClock,
+    // Loaded chunks whose light changed since they were meshed
+    dirty_chunks: HashSet<ChunkPos>,
+    // Loaded chunks being meshed again on the workers
+    remeshes_in_flight: HashMap<ChunkPos, ChunkJobHandle>,
+    workers: ChunkWorkers,
 }
 
 impl World {
-    pub fn new(display: &glium::Display<WindowSurface>) -> Self {
-        let mut chunks: Vec<Chunk> = Vec::new();
-        let chunk_size = 16; // Define chunk size
-        let grid_size = 13;  // Define grid size
-        let chunk_radius = 5; // Set how far chunks will be generated around the camera
-        let mut chunk_positions: HashSet<Point3<i32>> = HashSet::new();
-        let mut chunk_generation_queue: Vec<Point3<i32>> = Vec::new();
-
-        // Calculate the starting and ending positions for the grid
-        let half_grid_size = grid_size / 2;
-
-       // Generate a grid of chunks initially
-       for x in -half_grid_size..=half_grid_size {
-        for z in -half_grid_size..=half_grid_size {
-            let chunk_position = Point3::new(x, 0, z);
-            // Push new chunk into chunks vector
-            chunks.push(Chunk::new(display, chunk_position));
-            // Track chunk position in the HashSet
-            chunk_positions.insert(chunk_position);
-        }
-       }
+    pub fn new(preset: WorldPreset) -> Self {
+        let chunks: HashMap<ChunkPos, Chunk> = HashMap::new();
+        let render_distance = RenderDistance::new(6); // Set how far chunks will be generated around the camera
+        let generator = preset.generator_config(WORLD_SEED);
+        let workers = ChunkWorkers::new(generator.clone(), ChunkWorkers::default_thread_count());
 
-        World {
+        let mut world = World {
             chunks,
-            chunk_positions,
-            chunk_size,
-            last_camera_chunk_position: Point3::new(0, 0, 0), // Initial position
-            chunk_radius,
-            chunk_generation_queue
-        }
+            last_camera_chunk_position: ChunkPos::default(), // Initial position
+            render_distance,
+            frame_budget: Duration::from_millis(4),
+            chunk_generation_queue: ChunkLoadQueue::new(),
+            chunks_in_flight: HashMap::new(),
+            job_metrics: ChunkJobMetrics::default(),
+            chunk_cache: ChunkCache::new(CHUNK_CACHE_CAPACITY, true),
+            preset,
+            generator,
+            clock: WorldClock::default(),
+            dirty_chunks: HashSet::new(),
+            remeshes_in_flight: HashMap::new(),
+            workers,
+        };
+
+        // Queue the chunks around the spawn, the workers stream them in over the first frames
+        world.generate_chunks_around(world.last_camera_chunk_position);
+        world
+    }
+
+    // Change how far chunks are loaded around the camera, takes effect right away
+    pub fn set_render_distance(&mut self, load_radius: i32) {
+        self.render_distance = RenderDistance {
+            load_radius: load_radius.clamp(RenderDistance::MIN_RADIUS, RenderDistance::MAX_RADIUS),
+            ..self.render_distance
+        };
+        self.cancel_stale_jobs(self.last_camera_chunk_position);
+        self.generate_chunks_around(self.last_camera_chunk_position);
     }
 
-    pub fn update(&mut self, camera_position: Point3<f32>, display: &glium::Display<WindowSurface>) {
+    pub fn update(&mut self, camera_position: Point3<f64>, view_direction: Vector3<f32>, display: &glium::Display<WindowSurface>) {
+        let frame_start = Instant::now();
+
         // Convert the camera's world position to chunk coordinates
-        let camera_chunk_x = (camera_position.x / self.chunk_size as f32).floor() as i32;
-        let camera_chunk_z = (camera_position.z / self.chunk_size as f32).floor() as i32;
-        let camera_chunk_position = Point3::new(camera_chunk_x, 0, camera_chunk_z);
+        let camera_chunk_position = ChunkPos::column_containing(camera_position);
 
         // Check if the camera has moved to a new chunk
         if camera_chunk_position != self.last_camera_chunk_position {
-            // Generate and load new chunks around the new camera chunk position
+            // Stop working on chunks left behind, then queue the new ones around the camera
+            self.cancel_stale_jobs(camera_chunk_position);
             self.generate_chunks_around(camera_chunk_position);
 
-            // Unload far chunks
-            self.unload_distant_chunks(camera_chunk_position);
-
             // Update the last known camera chunk position
             self.last_camera_chunk_position = camera_chunk_position;
         }
 
-        let max_chunks_per_frame = 5;  // Adjust this number as needed to balance performance
-        self.process_chunk_generation_queue(display, max_chunks_per_frame);
+        // Chunks in view get generated first, the order follows the camera as it turns
+        self.chunk_generation_queue.update_view(camera_chunk_position, view_direction);
+
+        // All share the frame budget, anything left over carries on next frame
+        let deadline = frame_start + self.frame_budget;
+        self.unload_distant_chunks(camera_chunk_position, deadline);
+        self.process_chunk_generation_queue(display, deadline);
+        self.remesh_dirty_chunks(camera_chunk_position, display, deadline);
     }
 
-    // Generate chunks in a radius around the camera's current chunk
-    fn generate_chunks_around(&mut self, camera_chunk_position: Point3<i32>) {
-        let mut chunk_positions: Vec<Point3<i32>> = Vec::new();
-    
-        for x in -self.chunk_radius..=self.chunk_radius {
-            for z in -self.chunk_radius..=self.chunk_radius {
-                let chunk_position = Point3::new(
-                    camera_chunk_position.x + x,
-                    0,
-                    camera_chunk_position.z + z,
-                );
-                if !self.chunk_exists(chunk_position) {
-                    chunk_positions.push(chunk_position);
-                }
+    // Generate chunks in a circle around the camera's current chunk
+    fn generate_chunks_around(&mut self, camera_chunk_position: ChunkPos) {
+        let render_distance = self.render_distance;
+        for chunk_position in render_distance.chunks_to_load(camera_chunk_position) {
+            // The queue orders the chunks by distance and view direction
+            if !self.chunk_exists(chunk_position) {
+                self.chunk_generation_queue.push(chunk_position);
             }
         }
+    }
     
-        // Sort by distance to the camera chunk
-        chunk_positions.sort_by_key(|pos| {
-            let dx = (pos.x - camera_chunk_position.x).abs();
-            let dz = (pos.z - camera_chunk_position.z).abs();
-            dx + dz // Manhattan distance
+
+    // Drop queued chunks and cancel in-flight jobs that fell out of the load radius
+    fn cancel_stale_jobs(&mut self, camera_chunk_position: ChunkPos) {
+        let render_distance = self.render_distance;
+        self.job_metrics.dropped_from_queue += self
+            .chunk_generation_queue
+            .retain(|chunk_position| render_distance.should_load(chunk_position, camera_chunk_position));
+
+        let metrics = &mut self.job_metrics;
+        self.chunks_in_flight.retain(|&chunk_position, job| {
+            let keep = render_distance.should_load(chunk_position, camera_chunk_position);
+            if !keep {
+                job.cancel();
+                metrics.cancelled_in_flight += 1;
+            }
+            keep
         });
-    
-        // Add the sorted chunks to the generation queue
-        self.chunk_generation_queue.extend(chunk_positions);
     }
-    
 
-    fn process_chunk_generation_queue(&mut self, display: &glium::Display<WindowSurface>, max_chunks_per_frame: usize) {
-        for _ in 0..max_chunks_per_frame {
-            if let Some(chunk_position) = self.chunk_generation_queue.pop() {
-                self.chunks.push(Chunk::new(display, chunk_position));
-                self.chunk_positions.insert(chunk_position);
+    fn process_chunk_generation_queue(&mut self, display: &glium::Display<WindowSurface>, deadline: Instant) {
+        // Only keep a few jobs per worker in flight, so the queue still decides what gets generated next
+        let max_in_flight = self.workers.thread_count() * 2;
+        while self.chunks_in_flight.len() < max_in_flight && Instant::now() < deadline {
+            let Some(chunk_position) = self.chunk_generation_queue.pop() else {
+                break;
+            };
+            if self.chunk_exists(chunk_position) {
+                continue;
+            }
+
+            // Chunks unloaded not long ago skip generation, and lighting and meshing too if those were kept
+            let submitted = match self.chunk_cache.take(chunk_position) {
+                Some(CachedChunk { voxels, light: Some(light), chunk_data: Some(chunk_data) }) => {
+                    let cached = GeneratedChunk { position: chunk_position, voxels, light, chunk_data };
+                    self.insert_chunk(display, cached);
+                    continue;
+                }
+                Some(CachedChunk { voxels, light, .. }) => self.workers.submit_mesh(chunk_position, voxels, light),
+                None => self.workers.submit(chunk_position),
+            };
+            match submitted {
+                Ok(job) => {
+                    self.chunks_in_flight.insert(chunk_position, job);
+                    self.job_metrics.submitted += 1;
+                }
+                Err(stopped) => {
+                    // Without workers the chunk is built on this thread instead
+                    let generated = match (stopped.voxels, stopped.light) {
+                        (Some(voxels), Some(light)) => GeneratedChunk::with_light(chunk_position, voxels, light),
+                        (Some(voxels), None) => GeneratedChunk::from_voxels(chunk_position, voxels),
+                        (None, _) => GeneratedChunk::generate(chunk_position, &self.generator),
+                    };
+                    self.insert_chunk(display, generated);
+                }
             }
         }
-    
-        // Limit the queue size to avoid an ever-growing queue
-        if self.chunk_generation_queue.len() > 500 { // Arbitrary limit, adjust as needed
-            self.chunk_generation_queue.truncate(500);
+
+        // Generation and meshing already happened on the workers, only the upload is left for this thread.
+        // At least one chunk is uploaded every frame so loading never stalls on a slow frame.
+        loop {
+            let Some(finished) = self.workers.finished().next() else {
+                break;
+            };
+            if finished.remesh {
+                self.finish_remesh(display, finished);
+                if Instant::now() >= deadline {
+                    break;
+                }
+                continue;
+            }
+
+            // Jobs cancelled since they were submitted are no longer in flight
+            let current = self.chunks_in_flight.get(&finished.position).is_some_and(|job| job.id == finished.id);
+            match finished.chunk {
+                Some(generated) if current => {
+                    self.chunks_in_flight.remove(&generated.position);
+                    self.insert_chunk(display, generated);
+                    self.job_metrics.uploaded += 1;
+                }
+                Some(generated) => {
+                    // Nobody wants the chunk right now, but it may be wanted again soon
+                    if !self.chunk_exists(generated.position) {
+                        self.chunk_cache.insert(generated.position, generated.voxels, generated.light, generated.chunk_data);
+                    }
+                    self.job_metrics.discarded += 1;
+                }
+                None => {
+                    // A job that panicked is still in flight, the chunk is tried again once the camera moves on
+                    if current {
+                        self.chunks_in_flight.remove(&finished.position);
+                    }
+                    self.job_metrics.abandoned += 1;
+                }
+            }
+
+            if Instant::now() >= deadline {
+                break;
+            }
         }
     }
     
 
-    // Helper function to check if a chunk already exists at a given position
-    fn chunk_exists(&self, chunk_position: Point3<i32>) -> bool {
-        self.chunk_positions.contains(&chunk_position)
+    // Upload a chunk and let light flow between it and the loaded chunks next to it
+    fn insert_chunk(&mut self, display: &glium::Display<WindowSurface>, generated: GeneratedChunk) {
+        let position = generated.position;
+        self.chunks.insert(position, Chunk::upload(display, generated));
+        spread_between_chunks(self, position);
+
+        // Its border faces were meshed without the light of the chunks next to it, and theirs without its light
+        let neighbours: Vec<ChunkPos> = self.neighbours(position).map(|chunk| chunk.position).collect();
+        if !neighbours.is_empty() {
+            self.dirty_chunks.insert(position);
+            self.dirty_chunks.extend(neighbours);
+        }
     }
 
-    // Unload chunks that are far away from the camera
-    fn unload_distant_chunks(&mut self, camera_chunk_position: Point3<i32>) {
-        // Set the distance for unloading chunks (you can adjust this as needed)
-        let chunk_unload_distance = self.chunk_radius + 3;
-    
-        self.chunks.retain(|chunk| {
-            // Calculate the distance to the chunk from the camera's current position
-            let distance_x = (chunk.position.x - camera_chunk_position.x).abs();
-            let distance_z = (chunk.position.z - camera_chunk_position.z).abs();
-            
-            // Calculate the Manhattan distance
-            let is_within_unload_distance = distance_x <= chunk_unload_distance && distance_z <= chunk_unload_distance;
-    
-            // If the chunk is out of range, remove it from the set of chunk positions
-            if !is_within_unload_distance {
-                self.chunk_positions.remove(&chunk.position);  // Remove unloaded chunk from the set
+    // Mesh a loaded chunk with its light, looking into the chunks around it for the blocks and light in front of its border faces
+    fn mesh(&self, position: ChunkPos) -> Option<ChunkData> {
+        let chunk = self.chunk(position)?;
+        Some(mesh_chunk_with_borders(&chunk.voxels, &chunk.light, &self.border_blocks(position), &BorderLight::gather(self, position)))
+    }
+
+    fn border_blocks(&self, position: ChunkPos) -> BorderBlocks {
+        BorderBlocks::gather(position, |neighbour| self.chunk(neighbour).map(|chunk| &chunk.voxels))
+    }
+
+    // Remesh a chunk on this thread, a mesh of it still on the workers would be older so it's dropped
+    fn remesh(&mut self, position: ChunkPos, display: &glium::Display<WindowSurface>) {
+        if let Some(job) = self.remeshes_in_flight.remove(&position) {
+            job.cancel();
+        }
+        if let Some(chunk_data) = self.mesh(position) {
+            self.chunks.get_mut(&position).unwrap().replace_mesh(display, chunk_data);
+        }
+    }
+
+    // Hand the chunks whose light changed to the workers to remesh, closest to the camera first, until the deadline.
+    // A chunk that's already being remeshed stays dirty until that mesh is back, then goes again with its newer light.
+    fn remesh_dirty_chunks(&mut self, camera_chunk_position: ChunkPos, display: &glium::Display<WindowSurface>, deadline: Instant) {
+        let mut dirty: Vec<ChunkPos> =
+            self.dirty_chunks.iter().copied().filter(|position| !self.remeshes_in_flight.contains_key(position)).collect();
+        dirty.sort_by_key(|&position| chunk_distance_squared(position, camera_chunk_position));
+
+        let max_in_flight = self.workers.thread_count() * 2;
+        for (index, position) in dirty.into_iter().enumerate() {
+            if self.remeshes_in_flight.len() >= max_in_flight || (index > 0 && Instant::now() >= deadline) {
+                break;
             }
-            
-            // Retain chunks that are within the unload distance
-            is_within_unload_distance
-        });
+            self.dirty_chunks.remove(&position);
+            let Some(chunk) = self.chunk(position) else {
+                continue;
+            };
+
+            // The workers get a copy, the chunk's blocks and light can keep changing here meanwhile
+            let (border_blocks, border_light) = (self.border_blocks(position), BorderLight::gather(self, position));
+            match self.workers.submit_remesh(position, chunk.voxels.clone(), chunk.light.clone(), border_blocks, border_light) {
+                Ok(job) => {
+                    self.remeshes_in_flight.insert(position, job);
+                }
+                // Without workers the chunk is remeshed on this thread instead
+                Err(_) => self.remesh(position, display),
+            }
+        }
+    }
+
+    // Swap in a mesh that came back from the workers, unless the chunk was unloaded or remeshed here since
+    fn finish_remesh(&mut self, display: &glium::Display<WindowSurface>, finished: FinishedJob) {
+        let current = self.remeshes_in_flight.get(&finished.position).is_some_and(|job| job.id == finished.id);
+        if !current {
+            return;
+        }
+        self.remeshes_in_flight.remove(&finished.position);
+
+        match (finished.chunk, self.chunks.get_mut(&finished.position)) {
+            (Some(remeshed), Some(chunk)) => {
+                chunk.replace_mesh(display, remeshed.chunk_data);
+                self.job_metrics.remeshed += 1;
+            }
+            // It panicked, so it's tried again
+            (None, Some(_)) => {
+                self.dirty_chunks.insert(finished.position);
+                self.job_metrics.abandoned += 1;
+            }
+            (_, None) => {}
+        }
+    }
+
+    // Helper function to check if a chunk already exists or is being generated at a given position
+    fn chunk_exists(&self, chunk_position: ChunkPos) -> bool {
+        self.chunks.contains_key(&chunk_position) || self.chunks_in_flight.contains_key(&chunk_position)
+    }
+
+    // Unload chunks that are past the unload radius, until the frame's deadline
+    fn unload_distant_chunks(&mut self, camera_chunk_position: ChunkPos, deadline: Instant) {
+        let render_distance = self.render_distance;
+        let distant: Vec<ChunkPos> = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|&position| render_distance.should_unload(position, camera_chunk_position))
+            .collect();
+
+        for position in distant {
+            if Instant::now() >= deadline {
+                break;
+            }
+
+            // Frees the chunk's buffers, its blocks, light and mesh go to the cache
+            self.dirty_chunks.remove(&position);
+            if let Some(job) = self.remeshes_in_flight.remove(&position) {
+                job.cancel();
+            }
+            if let Some(chunk) = self.chunks.remove(&position) {
+                self.chunk_cache.insert(position, chunk.voxels, chunk.light, chunk.chunk_data);
+            }
+        }
+    }
+
+    pub fn chunk(&self, position: ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(&position)
+    }
+
+    pub fn chunk_mut(&mut self, position: ChunkPos) -> Option<&mut Chunk> {
+        self.chunks.get_mut(&position)
+    }
+
+    // Loaded chunks sharing a face with a chunk
+    pub fn neighbours(&self, position: ChunkPos) -> impl Iterator<Item = &Chunk> + '_ {
+        position.neighbours().into_iter().filter_map(move |neighbour| self.chunk(neighbour))
+    }
+
+    // Loaded chunks that aren't hidden behind others when looking out of the camera chunk,
+    // `None` if the camera chunk isn't loaded
+    pub fn visible_chunks(&self, camera_chunk: ChunkPos) -> Option<HashSet<ChunkPos>> {
+        visible_chunks(camera_chunk, |position| self.chunk(position).map(|chunk| chunk.chunk_data.visibility))
+    }
+
+    // Block at a world position, `None` if its chunk isn't loaded
+    pub fn block(&self, position: BlockPos) -> Option<Block> {
+        let (chunk, local) = position.split();
+        self.chunk(chunk).map(|chunk| chunk.voxels.get(local.x, local.y, local.z))
     }
+
+    // Change a block, update the light around it and rebuild the meshes it changed, returns false if its chunk isn't loaded
+    pub fn set_block(&mut self, position: BlockPos, block: Block, display: &glium::Display<WindowSurface>) -> bool {
+        let (chunk_position, local) = position.split();
+        let Some(chunk) = self.chunk_mut(chunk_position) else {
+            return false;
+        };
+        chunk.voxels.set(local.x, local.y, local.z, block);
+
+        // Only the chunks this change touched are remeshed right away, the others wait their turn.
+        // That includes the chunks next door, their border faces against this block come and go with it.
+        let waiting = std::mem::take(&mut self.dirty_chunks);
+        self.dirty_chunks.insert(chunk_position);
+        let neighbours: Vec<ChunkPos> = border_neighbours(chunk_position, local).filter(|neighbour| self.chunks.contains_key(neighbour)).collect();
+        self.dirty_chunks.extend(neighbours);
+        relight_block(self, position);
+        let changed = std::mem::replace(&mut self.dirty_chunks, waiting);
+        for position in changed {
+            self.dirty_chunks.remove(&position);
+            self.remesh(position, display);
+        }
+        true
+    }
+}
+
+impl LightWorld for World {
+    fn chunk_light(&self, position: ChunkPos) -> Option<(&ChunkVoxels, &ChunkLight)> {
+        self.chunk(position).map(|chunk| (&chunk.voxels, &chunk.light))
+    }
+
+    // Marks the chunks that have to be remeshed to show the new light
+    fn set_light(&mut self, position: BlockPos, light: Light) {
+        let (chunk_position, local) = position.split();
+        let Some(chunk) = self.chunks.get_mut(&chunk_position) else {
+            return;
+        };
+        chunk.light.set(local.x, local.y, local.z, light);
+        self.dirty_chunks.insert(chunk_position);
+
+        // Border faces of the chunk next door are lit by the blocks in front of them, on this side of the border
+        for neighbour in border_neighbours(chunk_position, local) {
+            if self.chunks.contains_key(&neighbour) {
+                self.dirty_chunks.insert(neighbour);
+            } else {
+                self.chunk_cache.forget_light(neighbour);
+            }
+        }
+    }
+}
+
+// The chunks a block on the border of its chunk is next to
+fn border_neighbours(chunk_position: ChunkPos, local: LocalPos) -> impl Iterator<Item = ChunkPos> {
+    [
+        (-1, 0, local.x == 0),
+        (1, 0, local.x == CHUNK_SIZE - 1),
+        (0, -1, local.z == 0),
+        (0, 1, local.z == CHUNK_SIZE - 1),
+    ]
+    .into_iter()
+    .filter(|&(_, _, on_border)| on_border)
+    .map(move |(dx, dz, _)| chunk_position.offset(dx, 0, dz))
 }
@@ -0,0 +1,95 @@
+use image::{Rgba, RgbaImage};
+use pixelquest::graphics::atlas::AtlasLayout;
+use pixelquest::graphics::texture::{get_uvs, init_uvs};
+use pixelquest::graphics::texture_pack::{TextureManifest, DEFAULT_TEXTURE_PACK};
+use pixelquest::world::block::Block;
+use pixelquest::world::light::ChunkLight;
+use pixelquest::world::mesher::mesh_chunk;
+use pixelquest::world::voxels::ChunkVoxels;
+
+extern crate pixelquest;
+
+const LAYOUT: AtlasLayout = AtlasLayout { tile_size: 4, padding: 2, page_size: 16 };
+
+#[test]
+fn test_tiles_are_packed_with_their_edges_repeated_around_them() {
+    // A tile with a different colour in every pixel
+    let tile = RgbaImage::from_fn(4, 4, |x, y| Rgba([x as u8 * 60, y as u8 * 60, 0, 255]));
+    let tiles = vec![RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])); 5].into_iter().chain([tile.clone()]).collect::<Vec<_>>();
+    let pages = LAYOUT.pack(&tiles);
+
+    // Two cells a row, four a page, so the sixth tile is the second on the second page
+    assert_eq!(pages.len(), 2);
+    let (page, x, y) = LAYOUT.tile_position(5);
+    assert_eq!((page, x, y), (1, 10, 2));
+    for dy in 0..4 {
+        for dx in 0..4 {
+            assert_eq!(pages[1].get_pixel(x + dx, y + dy), tile.get_pixel(dx, dy));
+        }
+    }
+    // The padding repeats the nearest edge pixel, corners included
+    assert_eq!(pages[1].get_pixel(x - 2, y), tile.get_pixel(0, 0));
+    assert_eq!(pages[1].get_pixel(x + 5, y + 2), tile.get_pixel(3, 2));
+    assert_eq!(pages[1].get_pixel(x + 5, y + 5), tile.get_pixel(3, 3));
+
+    // Pages are flipped when uploaded, so the tile's top row is at the top of its v range
+    let uvs = LAYOUT.tile_uvs(5);
+    assert_eq!(uvs.layer, 1);
+    assert_eq!(uvs.corners, [(0.625, 0.625), (0.875, 0.625), (0.875, 0.875), (0.625, 0.875)]);
+}
+
+#[test]
+fn test_grass_has_a_grass_top_and_dirt_sides() {
+    let mut voxels = ChunkVoxels::new();
+    voxels.set(3, 0, 3, Block::DarkGrass);
+    let chunk_data = mesh_chunk(&voxels, &ChunkLight::compute(&voxels));
+
+    let manifest = TextureManifest::load(DEFAULT_TEXTURE_PACK).unwrap();
+    let tile_uvs = |image: &str| manifest.layout().tile_uvs(manifest.images.iter().position(|known| known == image).unwrap() as u32);
+    let in_tile = |image: &str, tex_coords: [f32; 2]| {
+        let [(u1, v1), _, (u2, v2), _] = tile_uvs(image).corners;
+        (u1..=u2).contains(&tex_coords[0]) && (v1..=v2).contains(&tex_coords[1])
+    };
+
+    for vertex in &chunk_data.vertices {
+        let expected = if vertex.normal == [0.0, 1.0, 0.0] { "dark-grass.png" } else { "grass-side.png" };
+        assert!(in_tile(expected, vertex.tex_coords), "{:?} face isn't using {}", vertex.normal, expected);
+    }
+    assert_eq!(get_uvs(Block::DarkGrass).bottom, tile_uvs("brown.png"));
+    // Meshing already settled on the default pack, picking one now is too late
+    assert!(init_uvs(&manifest).is_err());
+}
+
+// Halve a page like the GPU does when it makes the next mipmap level, averaging 2x2 blocks of pixels
+fn downsample(page: &RgbaImage) -> RgbaImage {
+    RgbaImage::from_fn(page.width() / 2, page.height() / 2, |x, y| {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| page.get_pixel(x * 2 + dx, y * 2 + dy).0);
+        Rgba([0, 1, 2, 3].map(|channel| (block.iter().map(|pixel| pixel[channel] as u32).sum::<u32>() / 4) as u8))
+    })
+}
+
+#[test]
+fn test_mipmap_levels_dont_bleed_between_tiles() {
+    assert_eq!(AtlasLayout::default().mip_levels(), 4);
+
+    let layout = AtlasLayout::for_tile_size(8);
+    // Every tile has its own red, with green changing across it so averaging has something to do
+    let tiles: Vec<RgbaImage> = (0..20).map(|tile| RgbaImage::from_fn(8, 8, |x, y| Rgba([tile * 12, (x * 30 + y * 5) as u8, 0, 255]))).collect();
+    let mut page = layout.pack(&tiles).remove(0);
+
+    for level in 1..=layout.mip_levels() {
+        page = downsample(&page);
+        let cell_size = layout.cell_size() >> level;
+        for tile in 0..tiles.len() as u32 {
+            let (_, x, y) = layout.tile_position(tile);
+            let (cell_x, cell_y) = ((x - layout.padding) >> level, (y - layout.padding) >> level);
+            for dy in 0..cell_size {
+                for dx in 0..cell_size {
+                    assert_eq!(page.get_pixel(cell_x + dx, cell_y + dy)[0], tile as u8 * 12, "tile {} bled at level {}", tile, level);
+                }
+            }
+        }
+    }
+    // The smallest level still has padding to sample into at a tile's edge
+    assert!(layout.padding >> layout.mip_levels() >= 1);
+}
@@ -0,0 +1,50 @@
+use cgmath::{perspective, Deg, Point3, Vector3};
+use pixelquest::camera::camera::{camera_relative, Camera};
+use pixelquest::world::coords::ChunkPos;
+
+extern crate pixelquest;
+
+#[test]
+fn test_far_chunks_are_placed_exactly_relative_to_the_camera() {
+    // Thousands of chunks out, f32 world positions can't tell neighbouring blocks' corners apart anymore
+    let chunk = ChunkPos::new(2_000_000, 0, -3_000_000);
+    let camera_position = chunk.world_origin() + Vector3::new(20.25, 70.5, -3.125);
+
+    assert_eq!(camera_relative(chunk.world_origin(), camera_position), Vector3::new(-20.25, -70.5, 3.125));
+    assert_eq!(
+        camera_relative(chunk.offset(1, 0, 0).world_origin(), camera_position),
+        Vector3::new(16.0 - 20.25, -70.5, 3.125)
+    );
+}
+
+#[test]
+fn test_view_matrix_ignores_the_camera_position() {
+    let mut camera = Camera::new(Point3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), -90.0, 0.0);
+    let view = camera.get_view_matrix();
+    camera.translate(Vector3::new(1.0e7, 5.0, -1.0e7));
+
+    assert_eq!(camera.get_view_matrix(), view);
+    assert_eq!(camera.position, Point3::new(1.0e7, 5.0, -1.0e7));
+    assert_eq!(camera.relative_position(Point3::new(1.0e7 + 0.5, 5.0, -1.0e7)), Vector3::new(0.5, 0.0, 0.0));
+}
+
+#[test]
+fn test_frustum_culls_boxes_outside_the_view() {
+    // Looking down -z from the origin
+    let camera = Camera::new(Point3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), -90.0, 0.0);
+    let frustum = camera.frustum(perspective(Deg(90.0), 1.0, 0.1, 100.0));
+
+    assert!(frustum.contains_point(Point3::new(0.0, 0.0, -10.0)));
+    assert!(!frustum.contains_point(Point3::new(0.0, 0.0, 10.0)));
+    assert!(!frustum.contains_point(Point3::new(0.0, 0.0, -150.0)));
+
+    let visible = |min: [f32; 3], max: [f32; 3]| frustum.intersects_aabb(Point3::from(min), Point3::from(max));
+    assert!(visible([-1.0, -1.0, -11.0], [1.0, 1.0, -9.0]));
+    // Behind the camera, beyond the far plane and off to the side
+    assert!(!visible([-1.0, -1.0, 9.0], [1.0, 1.0, 11.0]));
+    assert!(!visible([-1.0, -1.0, -210.0], [1.0, 1.0, -200.0]));
+    assert!(!visible([30.0, -1.0, -11.0], [32.0, 1.0, -9.0]));
+    // Boxes the camera is inside of or that only poke into the view are kept
+    assert!(visible([-8.0, -8.0, -8.0], [8.0, 8.0, 8.0]));
+    assert!(visible([10.0, -1.0, -11.0], [30.0, 1.0, -9.0]));
+}
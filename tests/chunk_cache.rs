@@ -0,0 +1,83 @@
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::block::Block;
+use pixelquest::world::chunk::ChunkData;
+use pixelquest::world::chunk_cache::ChunkCache;
+use pixelquest::world::light::ChunkLight;
+use pixelquest::world::mesher::mesh_chunk;
+use pixelquest::world::voxels::ChunkVoxels;
+
+extern crate pixelquest;
+
+fn chunk(block: Block) -> (ChunkVoxels, ChunkLight, ChunkData) {
+    let mut voxels = ChunkVoxels::new();
+    voxels.set(3, 4, 5, block);
+    let light = ChunkLight::compute(&voxels);
+    let chunk_data = mesh_chunk(&voxels, &light);
+    (voxels, light, chunk_data)
+}
+
+#[test]
+fn test_cache_gives_chunks_back_and_counts_lookups() {
+    let mut cache = ChunkCache::new(16 * 1024 * 1024, true);
+    let (voxels, light, chunk_data) = chunk(Block::Rock);
+    cache.insert(ChunkPos::new(1, 0, 2), voxels, light, chunk_data);
+
+    assert!(cache.take(ChunkPos::new(2, 0, 1)).is_none());
+    let cached = cache.take(ChunkPos::new(1, 0, 2)).expect("chunk wasn't cached");
+    assert_eq!(cached.voxels.get(3, 4, 5), Block::Rock);
+    assert!(cached.light.is_some());
+    assert_eq!(cached.chunk_data.expect("mesh wasn't kept").indices.len(), 36);
+
+    // Taking a chunk removes it from the cache
+    assert!(cache.take(ChunkPos::new(1, 0, 2)).is_none());
+    assert_eq!((cache.hits(), cache.misses()), (1, 2));
+    assert!(cache.is_empty());
+    assert_eq!(cache.memory_used(), 0);
+}
+
+#[test]
+fn test_cache_evicts_the_least_recently_unloaded_chunks() {
+    let chunk_size = ChunkVoxels::new().memory_size() + ChunkLight::new().memory_size();
+    let mut cache = ChunkCache::new(chunk_size * 3, false);
+
+    for x in 0..5 {
+        let (voxels, light, chunk_data) = chunk(Block::Dirt);
+        cache.insert(ChunkPos::new(x, 0, 0), voxels, light, chunk_data);
+        assert!(cache.memory_used() <= chunk_size * 3);
+    }
+
+    assert_eq!(cache.len(), 3);
+    assert!(!cache.contains(ChunkPos::new(0, 0, 0)));
+    assert!(!cache.contains(ChunkPos::new(1, 0, 0)));
+    assert!((2..5).all(|x| cache.contains(ChunkPos::new(x, 0, 0))));
+
+    // Unloading a cached chunk again makes it the most recent one
+    let (voxels, light, chunk_data) = chunk(Block::Dirt);
+    cache.insert(ChunkPos::new(2, 0, 0), voxels, light, chunk_data);
+    let (voxels, light, chunk_data) = chunk(Block::Dirt);
+    cache.insert(ChunkPos::new(5, 0, 0), voxels, light, chunk_data);
+    assert!(cache.contains(ChunkPos::new(2, 0, 0)));
+    assert!(!cache.contains(ChunkPos::new(3, 0, 0)));
+
+    // Only the blocks and light are kept when meshes aren't
+    let cached = cache.take(ChunkPos::new(5, 0, 0)).unwrap();
+    assert!(cached.light.is_some() && cached.chunk_data.is_none());
+}
+
+#[test]
+fn test_forgetting_the_light_of_a_cached_chunk_keeps_its_blocks() {
+    let mut cache = ChunkCache::new(16 * 1024 * 1024, true);
+    let (voxels, light, chunk_data) = chunk(Block::Rock);
+    cache.insert(ChunkPos::new(0, 0, 0), voxels, light, chunk_data);
+    let used = cache.memory_used();
+
+    cache.forget_light(ChunkPos::new(0, 0, 0));
+    // Chunks that aren't cached are left alone
+    cache.forget_light(ChunkPos::new(1, 0, 0));
+    assert!(cache.memory_used() < used);
+
+    let cached = cache.take(ChunkPos::new(0, 0, 0)).unwrap();
+    assert_eq!(cached.voxels.get(3, 4, 5), Block::Rock);
+    assert!(cached.light.is_none() && cached.chunk_data.is_none());
+    assert_eq!(cache.memory_used(), 0);
+}
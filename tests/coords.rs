@@ -0,0 +1,68 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::coords::{BlockPos, ChunkPos, LocalPos};
+use pixelquest::world::voxels::CHUNK_HEIGHT;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate pixelquest;
+
+#[test]
+fn test_block_positions_round_trip_through_their_chunk() {
+    let mut rng = StdRng::seed_from_u64(39);
+    for _ in 0..10_000 {
+        let block = BlockPos::new(rng.gen_range(-100_000..100_000), rng.gen_range(-600..600), rng.gen_range(-100_000..100_000));
+        let (chunk, local) = block.split();
+
+        assert!(local.in_bounds(), "{:?} split into {:?}", block, local);
+        assert_eq!(chunk.block(local), block);
+        assert_eq!(chunk.origin().chunk(), chunk);
+        assert_eq!(chunk.origin().local(), LocalPos::new(0, 0, 0));
+        assert_eq!(chunk.block(LocalPos::new(CHUNK_SIZE - 1, CHUNK_HEIGHT - 1, CHUNK_SIZE - 1)).chunk(), chunk);
+    }
+}
+
+#[test]
+fn test_world_points_fall_in_the_block_around_them() {
+    let mut rng = StdRng::seed_from_u64(40);
+    for _ in 0..10_000 {
+        let block = BlockPos::new(rng.gen_range(-5_000..5_000), rng.gen_range(-300..300), rng.gen_range(-5_000..5_000));
+        let center = block.center();
+        // Anywhere inside the block's cube, which spans half a block either side of its centre
+        let point = Point3::new(
+            center.x + rng.gen_range(-0.49..0.49),
+            center.y + rng.gen_range(-0.49..0.49),
+            center.z + rng.gen_range(-0.49..0.49),
+        );
+
+        assert_eq!(BlockPos::containing(point), block);
+        assert_eq!(ChunkPos::containing(point), block.chunk());
+    }
+}
+
+#[test]
+fn test_neighbours_share_a_face() {
+    let block = BlockPos::new(-1, 0, 15);
+    let neighbours = block.neighbours();
+    assert_eq!(neighbours.len(), 6);
+    for neighbour in neighbours {
+        let distance = (neighbour.x - block.x).abs() + (neighbour.y - block.y).abs() + (neighbour.z - block.z).abs();
+        assert_eq!(distance, 1);
+    }
+
+    // Stepping off the edge of a chunk lands in the neighbouring chunk
+    let chunk_neighbours: Vec<ChunkPos> = neighbours.iter().map(|neighbour| neighbour.chunk()).collect();
+    assert!(chunk_neighbours.contains(&ChunkPos::new(0, 0, 0)));
+    assert!(chunk_neighbours.contains(&ChunkPos::new(-1, 0, 1)));
+    assert!(chunk_neighbours.contains(&ChunkPos::new(-1, -1, 0)));
+    assert!(block.chunk().neighbours().contains(&ChunkPos::new(0, 0, 0)));
+}
+
+#[test]
+fn test_camera_columns_stay_on_the_chunk_layer() {
+    // Below the bottom of the world and above its top the camera is still over the same column
+    for y in [-40.0, -0.6, 0.0, 255.6, 300.0] {
+        assert_eq!(ChunkPos::column_containing(Point3::new(-20.0, y, 35.0)), ChunkPos::new(-2, 0, 2));
+    }
+    assert_eq!(ChunkPos::containing(Point3::new(-20.0, -0.6, 35.0)), ChunkPos::new(-2, -1, 2));
+}
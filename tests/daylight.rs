@@ -0,0 +1,55 @@
+use pixelquest::renderer::daylight::{daylight, moon_direction, sun_direction};
+use pixelquest::world::clock::WorldClock;
+
+extern crate pixelquest;
+
+#[test]
+fn test_sun_is_up_by_day_and_the_moon_by_night() {
+    let noon = daylight(WorldClock::NOON);
+    let midnight = daylight(WorldClock::MIDNIGHT);
+
+    assert!(sun_direction(WorldClock::NOON).y > 0.9);
+    assert!(moon_direction(WorldClock::MIDNIGHT).y > 0.9);
+    assert_eq!(noon.light_direction, sun_direction(WorldClock::NOON));
+    assert_eq!(midnight.light_direction, moon_direction(WorldClock::MIDNIGHT));
+
+    assert!(noon.light_strength > midnight.light_strength);
+    assert!(noon.ambient_strength > midnight.ambient_strength);
+    assert!(noon.sky.zenith.iter().sum::<f32>() > midnight.sky.zenith.iter().sum::<f32>());
+}
+
+#[test]
+fn test_lighting_changes_smoothly_through_the_day() {
+    let light = |time_of_day: f32| {
+        let daylight = daylight(time_of_day);
+        let colour: Vec<f32> = daylight.light_colour.iter().map(|channel| channel * daylight.light_strength).collect();
+        (colour, daylight.ambient_strength, daylight.sky.horizon)
+    };
+
+    // Nothing jumps between two moments a minute apart, not even when the moon takes over from the sun
+    let steps = 24 * 60;
+    for step in 0..steps {
+        let (colour, ambient, horizon) = light(step as f32 / steps as f32);
+        let (next_colour, next_ambient, next_horizon) = light((step + 1) as f32 / steps as f32);
+        assert!(colour.iter().zip(&next_colour).all(|(a, b)| (a - b).abs() < 0.01), "light jumps at step {}", step);
+        assert!((ambient - next_ambient).abs() < 0.02);
+        assert!(horizon.iter().zip(&next_horizon).all(|(a, b)| (a - b).abs() < 0.02));
+    }
+}
+
+#[test]
+fn test_clock_advances_wraps_and_freezes() {
+    let mut clock = WorldClock::new(WorldClock::SUNSET, 100.0);
+
+    clock.advance(50.0);
+    assert_eq!(clock.day(), 1);
+    assert!((clock.time_of_day() - 0.25).abs() < 1e-5);
+
+    clock.frozen = true;
+    clock.advance(30.0);
+    assert!((clock.time_of_day() - 0.25).abs() < 1e-5);
+
+    clock.set_time_of_day(WorldClock::NOON);
+    assert_eq!(clock.day(), 1);
+    assert_eq!(clock.time_of_day(), WorldClock::NOON);
+}
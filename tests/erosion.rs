@@ -0,0 +1,127 @@
+use pixelquest::world::erosion::{erode_region, thermal_erosion, ErosionKind, ErosionRegions, ErosionSettings};
+use pixelquest::world::noise_layer::{Fractal, NoiseLayer, NoiseSettings};
+
+extern crate pixelquest;
+
+fn ridged_noise() -> NoiseLayer {
+    NoiseLayer::new(&NoiseSettings {
+        fractal: Fractal::Ridged,
+        seed: 142,
+        frequency: 0.02,
+        octaves: 5,
+        ..Default::default()
+    })
+}
+
+fn settings(kind: ErosionKind) -> ErosionSettings {
+    ErosionSettings {
+        kind,
+        region_size: 32,
+        margin: 8,
+        blend: 6,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_erosion_is_deterministic() {
+    let noise = ridged_noise();
+    let base = |x: i32, z: i32| noise.get(x as f64, z as f64) * 40.0;
+
+    for kind in [ErosionKind::thermal(), ErosionKind::hydraulic()] {
+        let first = erode_region(&base, (-1, 2), &settings(kind));
+        let second = erode_region(&base, (-1, 2), &settings(kind));
+        assert_eq!(first, second);
+        assert!(first.iter().all(|height| height.is_finite()));
+    }
+}
+
+#[test]
+fn test_eroded_regions_cross_fade_at_borders() {
+    let noise = ridged_noise();
+    let base = |x: i32, z: i32| noise.get(x as f64, z as f64) * 40.0;
+
+    for kind in [ErosionKind::thermal(), ErosionKind::hydraulic()] {
+        let settings = settings(kind);
+        let regions = ErosionRegions::new(settings);
+        let (region_size, margin) = (settings.region_size, settings.margin);
+        let size = region_size + 2 * margin;
+        let left = erode_region(&base, (0, 0), &settings);
+        let right = erode_region(&base, (1, 0), &settings);
+        let eroded = |heights: &[f32], region_x: i32, x: i32, z: i32| {
+            heights[((x - region_x * region_size + margin) * size + z + margin) as usize] as f64
+        };
+
+        // Away from the border every column is its own region's eroded height, and near it
+        // the column lies between what the two overlapping regions eroded it to
+        let z = region_size / 2;
+        for x in region_size / 2..region_size + region_size / 2 {
+            let height = regions.height(&base, x, z);
+            let (own, other) = if x < region_size { (eroded(&left, 0, x, z), 1) } else { (eroded(&right, 1, x, z), 0) };
+            if (x - region_size).abs() >= settings.blend {
+                assert!((height - own).abs() < 1e-4, "column {} isn't its region's eroded height", x);
+            } else {
+                let other = eroded([&left, &right][other], other as i32, x, z);
+                assert!(height >= own.min(other) - 1e-4 && height <= own.max(other) + 1e-4, "column {} isn't blended", x);
+            }
+        }
+
+        // The border columns are eroded too, not left at the base height
+        let changed = (-region_size..region_size)
+            .filter(|&i| (regions.height(&base, region_size, i) - base(region_size, i)).abs() > 0.01)
+            .count();
+        assert!(changed > 0);
+
+        // Sampling the same columns again doesn't erode anything new
+        let cached = regions.cached_regions();
+        assert!(regions.height(&base, 5, 5).is_finite());
+        assert_eq!(regions.cached_regions(), cached);
+    }
+}
+
+#[test]
+fn test_least_recently_sampled_regions_are_dropped() {
+    let noise = ridged_noise();
+    let base = |x: i32, z: i32| noise.get(x as f64, z as f64) * 40.0;
+    let regions = ErosionRegions::new(ErosionSettings {
+        max_cached_regions: 2,
+        ..settings(ErosionKind::thermal())
+    });
+
+    // The middle of three regions far apart, then the first one again
+    for (x, z) in [(16, 16), (16 + 32 * 5, 16), (16 + 32 * 10, 16), (16, 16)] {
+        let height = regions.height(&base, x, z);
+        assert!(regions.cached_regions() <= 2);
+        assert!((height - regions.height(&base, x, z)).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_hydraulic_erosion_changes_the_interior() {
+    let noise = ridged_noise();
+    let base = |x: i32, z: i32| noise.get(x as f64, z as f64) * 40.0;
+    let regions = ErosionRegions::new(settings(ErosionKind::hydraulic()));
+
+    let changed = (8..24)
+        .flat_map(|x| (8..24).map(move |z| (x, z)))
+        .filter(|&(x, z)| (regions.height(&base, x, z) - base(x, z)).abs() > 0.01)
+        .count();
+    assert!(changed > 0);
+}
+
+#[test]
+fn test_thermal_erosion_flattens_steep_slopes() {
+    let size = 16;
+    let mut heights: Vec<f32> = (0..size * size).map(|i| if (i / size) < size / 2 { 0.0 } else { 20.0 }).collect();
+    let steepest = |heights: &[f32]| {
+        (0..size - 1)
+            .flat_map(|x| (0..size).map(move |z| (x, z)))
+            .map(|(x, z)| (heights[(x + 1) * size + z] - heights[x * size + z]).abs())
+            .fold(0.0, f32::max)
+    };
+
+    let before = steepest(&heights);
+    thermal_erosion(&mut heights, size, 50, 1.0, 0.5);
+
+    assert!(steepest(&heights) < before);
+}
@@ -0,0 +1,64 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::block::Block;
+use pixelquest::world::chunk::generate_chunk;
+use pixelquest::world::generator::{GeneratorConfig, GeneratorPass};
+use pixelquest::world::heightfield::column_world_position;
+use pixelquest::world::noise_layer::{Fractal, NoiseSettings};
+
+extern crate pixelquest;
+
+// Only ground, so the highest block of every column is the surface
+fn mountain_config() -> GeneratorConfig {
+    let passes = vec![
+        GeneratorPass::Flat { height: 0, block: Block::Dirt },
+        GeneratorPass::Mountains {
+            noise: NoiseSettings {
+                fractal: Fractal::Ridged,
+                seed: 142,
+                frequency: 0.01,
+                octaves: 5,
+                ..Default::default()
+            },
+            erosion: None,
+            height_scale: 60.0,
+            upper_block: Block::DarkGrass,
+            lower_block: Block::Dirt,
+        },
+    ];
+    GeneratorConfig::new(142, 0, passes)
+}
+
+#[test]
+fn test_neighbouring_chunks_agree_on_border_heights() {
+    let config = mountain_config();
+    let surface = config.surface_height_field();
+    let surface_at = |chunk_position: Point3<i32>, x: i32, z: i32| {
+        let (world_x, world_z) = column_world_position(chunk_position, x, z);
+        Some(surface.sample(world_x, world_z))
+    };
+
+    for chunk_x in -1..1 {
+        for chunk_z in -1..1 {
+            let position = Point3::new(chunk_x, 0, chunk_z);
+            let east_position = Point3::new(chunk_x + 1, 0, chunk_z);
+            let south_position = Point3::new(chunk_x, 0, chunk_z + 1);
+            let chunk = generate_chunk(position, &config);
+            let east = generate_chunk(east_position, &config);
+            let south = generate_chunk(south_position, &config);
+
+            // The columns either side of a border rise and fall as the one world surface does
+            let last = CHUNK_SIZE - 1;
+            for i in 0..CHUNK_SIZE {
+                assert_eq!(
+                    (chunk.surface_height(last, i), east.surface_height(0, i)),
+                    (surface_at(position, last, i), surface_at(east_position, 0, i)),
+                );
+                assert_eq!(
+                    (chunk.surface_height(i, last), south.surface_height(i, 0)),
+                    (surface_at(position, i, last), surface_at(south_position, i, 0)),
+                );
+            }
+        }
+    }
+}
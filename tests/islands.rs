@@ -0,0 +1,74 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::block::Block;
+use pixelquest::world::noise_layer::{Fractal, NoiseKind, NoiseLayer, NoiseSettings};
+use pixelquest::world::terrain::generate_floating_islands;
+use pixelquest::world::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+extern crate pixelquest;
+
+fn island_chunk(chunk_position: Point3<i32>) -> ChunkVoxels {
+    let coverage = NoiseLayer::new(&NoiseSettings {
+        kind: NoiseKind::OpenSimplex,
+        fractal: Fractal::Fbm,
+        seed: 149,
+        frequency: 0.012,
+        octaves: 3,
+        ..Default::default()
+    });
+    let shape = NoiseLayer::new(&NoiseSettings {
+        fractal: Fractal::Fbm,
+        seed: 150,
+        frequency: 0.06,
+        octaves: 3,
+        ..Default::default()
+    });
+
+    let mut voxels = ChunkVoxels::new();
+    generate_floating_islands(chunk_position, 0, &mut voxels, &coverage, &shape, 90, 16, Block::LightGrass, Block::Rock, Block::Water);
+    voxels
+}
+
+fn blocks(voxels: &ChunkVoxels) -> impl Iterator<Item = (i32, Block)> + '_ {
+    (0..CHUNK_SIZE).flat_map(move |x| {
+        (0..CHUNK_SIZE).flat_map(move |z| (0..CHUNK_HEIGHT).map(move |y| (y, voxels.get(x, y, z))))
+    })
+}
+
+#[test]
+fn test_floating_islands_are_deterministic() {
+    let first = island_chunk(Point3::new(2, 0, -3));
+    let second = island_chunk(Point3::new(2, 0, -3));
+
+    assert!(blocks(&first).eq(blocks(&second)));
+}
+
+#[test]
+fn test_floating_islands_float_with_waterfalls() {
+    let mut island_blocks = 0;
+    let mut waterfall_blocks = 0;
+
+    for x in -6..6 {
+        for z in -6..6 {
+            for (y, block) in blocks(&island_chunk(Point3::new(x, 0, z))) {
+                match block {
+                    Block::Air => (),
+                    Block::LightGrass | Block::Rock => {
+                        // Islands stay up in the sky band
+                        assert!(y > 60 && y < 110, "island block at height {}", y);
+                        island_blocks += 1;
+                    }
+                    Block::Water => {
+                        // Waterfalls reach all the way down to the ground
+                        assert!(y > 0 && y < 110, "water block at height {}", y);
+                        waterfall_blocks += 1;
+                    }
+                    block => panic!("unexpected block {:?}", block),
+                }
+            }
+        }
+    }
+
+    assert!(island_blocks > 0);
+    assert!(waterfall_blocks > 0);
+}
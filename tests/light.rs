@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use pixelquest::world::block::Block;
+use pixelquest::world::coords::{BlockPos, ChunkPos, LocalPos};
+use pixelquest::world::light::{relight_block, spread_between_chunks, BorderLight, ChunkLight, Light, LightWorld, MAX_LIGHT};
+use pixelquest::world::voxels::ChunkVoxels;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate pixelquest;
+
+// Chunks and their light, without anything to draw them with
+struct TestWorld {
+    chunks: HashMap<ChunkPos, (ChunkVoxels, ChunkLight)>,
+}
+
+impl LightWorld for TestWorld {
+    fn chunk_light(&self, position: ChunkPos) -> Option<(&ChunkVoxels, &ChunkLight)> {
+        self.chunks.get(&position).map(|(voxels, light)| (voxels, light))
+    }
+
+    fn set_light(&mut self, position: BlockPos, light: Light) {
+        let (chunk, local) = position.split();
+        if let Some((_, chunk_light)) = self.chunks.get_mut(&chunk) {
+            chunk_light.set(local.x, local.y, local.z, light);
+        }
+    }
+}
+
+impl TestWorld {
+    // Loads the chunks one after the other, like the world does
+    fn load(chunks: Vec<(ChunkPos, ChunkVoxels)>) -> Self {
+        let mut world = TestWorld { chunks: HashMap::new() };
+        for (position, voxels) in chunks {
+            let light = ChunkLight::compute(&voxels);
+            world.chunks.insert(position, (voxels, light));
+            spread_between_chunks(&mut world, position);
+        }
+        world
+    }
+
+    fn set_block(&mut self, position: BlockPos, block: Block) {
+        let (chunk, local) = position.split();
+        self.chunks.get_mut(&chunk).unwrap().0.set(local.x, local.y, local.z, block);
+        relight_block(self, position);
+    }
+
+    fn light_at(&self, position: BlockPos) -> Light {
+        self.light(position).unwrap()
+    }
+}
+
+// A chunk with a solid floor and a roof over all of it, an empty cave in between
+fn cave() -> ChunkVoxels {
+    let mut voxels = ChunkVoxels::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..40 {
+                voxels.set(x, y, z, Block::Rock);
+            }
+            voxels.set(x, 60, z, Block::Rock);
+        }
+    }
+    voxels
+}
+
+#[test]
+fn test_sunlight_falls_from_the_sky_and_torches_light_caves() {
+    let mut voxels = cave();
+    voxels.set(8, 45, 8, Block::Torch);
+    let world = TestWorld::load(vec![(ChunkPos::new(0, 0, 0), voxels)]);
+
+    // Open sky above the roof, nothing reaches the cave under it
+    assert_eq!(world.light_at(BlockPos::new(3, 61, 3)), Light::SKY);
+    assert_eq!(world.light_at(BlockPos::new(3, 200, 3)), Light::SKY);
+    assert_eq!(world.light_at(BlockPos::new(3, 50, 3)).sun, 0);
+
+    // Torch light fades by a level every block
+    assert_eq!(world.light_at(BlockPos::new(8, 45, 8)).block, 14);
+    assert_eq!(world.light_at(BlockPos::new(8, 46, 8)).block, 13);
+    assert_eq!(world.light_at(BlockPos::new(11, 47, 8)).block, 9);
+    // Solid blocks stay dark
+    assert_eq!(world.light_at(BlockPos::new(8, 39, 8)), Light::default());
+}
+
+#[test]
+fn test_light_spreads_across_chunk_borders() {
+    // A torch right on the border, and an open chunk next to a covered one
+    let mut covered = cave();
+    covered.set(15, 45, 4, Block::Torch);
+    let mut open = cave();
+    for x in 0..16 {
+        for z in 0..16 {
+            open.set(x, 60, z, Block::Air);
+        }
+    }
+    let world = TestWorld::load(vec![(ChunkPos::new(0, 0, 0), covered), (ChunkPos::new(1, 0, 0), open)]);
+
+    assert_eq!(world.light_at(BlockPos::new(16, 45, 4)).block, 13);
+    assert_eq!(world.light_at(BlockPos::new(18, 45, 4)).block, 11);
+    // Sunlight from the open chunk creeps in under the edge of the roof
+    assert_eq!(world.light_at(BlockPos::new(16, 50, 4)).sun, MAX_LIGHT);
+    assert_eq!(world.light_at(BlockPos::new(15, 50, 4)).sun, MAX_LIGHT - 1);
+    assert_eq!(world.light_at(BlockPos::new(13, 50, 4)).sun, MAX_LIGHT - 3);
+}
+
+#[test]
+fn test_border_light_is_the_light_next_to_a_chunk() {
+    let mut covered = cave();
+    covered.set(15, 45, 4, Block::Torch);
+    let world = TestWorld::load(vec![(ChunkPos::new(0, 0, 0), covered), (ChunkPos::new(1, 0, 0), cave())]);
+
+    let border = BorderLight::gather(&world, ChunkPos::new(1, 0, 0));
+    for z in 0..16 {
+        for y in 0..256 {
+            assert_eq!(border.get(-1, y, z), Some(world.light_at(BlockPos::new(15, y, z))));
+        }
+    }
+    assert_eq!(border.get(-1, 45, 4).unwrap().block, 14);
+    // Nothing is loaded on the other sides, and inside, above and below the chunk isn't its border
+    assert_eq!(border.get(16, 45, 4), None);
+    assert_eq!(border.get(4, 45, -1), None);
+    assert_eq!(border.get(0, 45, 4), None);
+    assert_eq!(border.get(-1, 256, 4), None);
+    assert_eq!(border.get(-1, 45, -1), None);
+}
+
+#[test]
+fn test_changing_blocks_leaves_the_same_light_as_working_it_out_again() {
+    let positions = [ChunkPos::new(0, 0, 0), ChunkPos::new(1, 0, 0), ChunkPos::new(0, 0, 1)];
+    let mut world = TestWorld::load(positions.iter().map(|&position| (position, cave())).collect());
+
+    // Taking away brighter lava doesn't put out the torch next to it
+    world.set_block(BlockPos::new(5, 45, 5), Block::Torch);
+    world.set_block(BlockPos::new(6, 45, 5), Block::Lava);
+    world.set_block(BlockPos::new(6, 45, 5), Block::Air);
+    assert_eq!(world.light_at(BlockPos::new(5, 45, 5)).block, 14);
+    assert_eq!(world.light_at(BlockPos::new(7, 45, 5)).block, 12);
+
+    // Torches, lava, water and rock put down and dug out again around the cave and its roof
+    let blocks = [Block::Air, Block::Rock, Block::Torch, Block::Lava, Block::Water];
+    let mut rng = StdRng::seed_from_u64(50);
+    for _ in 0..300 {
+        let position = BlockPos::new(rng.gen_range(0..32), rng.gen_range(38..64), rng.gen_range(0..32));
+        if world.block(position).is_none() {
+            continue;
+        }
+        world.set_block(position, blocks[rng.gen_range(0..blocks.len())]);
+    }
+
+    let fresh = TestWorld::load(positions.iter().map(|&position| (position, world.chunks[&position].0.clone())).collect());
+    for &chunk in &positions {
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 0..256 {
+                    let position = chunk.block(LocalPos::new(x, y, z));
+                    assert_eq!(world.light_at(position), fresh.light_at(position), "light at {:?}", position);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use cgmath::Vector3;
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::load_queue::{chunk_priority, ChunkLoadQueue};
+
+extern crate pixelquest;
+
+fn queue_square(queue: &mut ChunkLoadQueue, radius: i32) {
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            queue.push(ChunkPos::new(x, 0, z));
+        }
+    }
+}
+
+#[test]
+fn test_chunks_in_view_are_loaded_first() {
+    let origin = ChunkPos::new(0, 0, 0);
+    let forward = Vector3::new(0.0, 0.0, -1.0);
+
+    // Closer always wins when looking the same way
+    assert!(chunk_priority(ChunkPos::new(0, 0, -1), origin, forward) < chunk_priority(ChunkPos::new(0, 0, -2), origin, forward));
+    // At the same distance, in front beats the side beats behind
+    let ahead = chunk_priority(ChunkPos::new(0, 0, -3), origin, forward);
+    let side = chunk_priority(ChunkPos::new(3, 0, 0), origin, forward);
+    let behind = chunk_priority(ChunkPos::new(0, 0, 3), origin, forward);
+    assert!(ahead < side && side < behind);
+
+    let mut queue = ChunkLoadQueue::new();
+    queue.update_view(origin, forward);
+    queue_square(&mut queue, 4);
+    assert_eq!(queue.pop(), Some(origin));
+    assert_eq!(queue.pop(), Some(ChunkPos::new(0, 0, -1)));
+}
+
+#[test]
+fn test_queue_follows_the_camera_turning() {
+    let mut queue = ChunkLoadQueue::new();
+    queue.update_view(ChunkPos::new(0, 0, 0), Vector3::new(0.0, 0.0, -1.0));
+    queue.push(ChunkPos::new(0, 0, -3));
+    queue.push(ChunkPos::new(0, 0, 3));
+
+    // Turn around before anything was loaded
+    queue.update_view(ChunkPos::new(0, 0, 0), Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(queue.pop(), Some(ChunkPos::new(0, 0, 3)));
+    assert_eq!(queue.pop(), Some(ChunkPos::new(0, 0, -3)));
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn test_queue_never_drops_chunks() {
+    let mut queue = ChunkLoadQueue::new();
+    queue_square(&mut queue, 20);
+    // Queuing a chunk twice doesn't load it twice
+    queue_square(&mut queue, 20);
+    assert_eq!(queue.len(), 41 * 41);
+
+    let mut popped = HashSet::new();
+    while let Some(position) = queue.pop() {
+        assert!(popped.insert(position));
+        if popped.len() % 100 == 0 {
+            queue.update_view(ChunkPos::new(popped.len() as i32 / 100, 0, 0), Vector3::new(1.0, 0.0, 0.0));
+        }
+    }
+    assert_eq!(popped.len(), 41 * 41);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_stale_chunks_can_be_dropped() {
+    let mut queue = ChunkLoadQueue::new();
+    queue_square(&mut queue, 3);
+
+    let dropped = queue.retain(|position| position.x >= 0);
+    assert_eq!(dropped, 3 * 7);
+    assert!(!queue.contains(ChunkPos::new(-1, 0, 0)));
+
+    // Dropped chunks can be queued again
+    queue.push(ChunkPos::new(-1, 0, 0));
+    assert_eq!(queue.len(), 4 * 7 + 1);
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use pixelquest::world::block::Block;
+use pixelquest::world::border::BorderBlocks;
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::light::{BorderLight, ChunkLight};
+use pixelquest::world::mesher::{mesh_chunk, mesh_chunk_with_borders};
+use pixelquest::world::voxels::ChunkVoxels;
+
+extern crate pixelquest;
+
+// Solid ground up to `height` in every column
+fn ground(height: i32) -> ChunkVoxels {
+    let mut voxels = ChunkVoxels::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..=height {
+                voxels.set(x, y, z, Block::Rock);
+            }
+        }
+    }
+    voxels
+}
+
+#[test]
+fn test_border_faces_against_loaded_neighbours_are_culled() {
+    let low = ground(3);
+    let chunks = HashMap::from([(ChunkPos::new(0, 0, 0), low.clone()), (ChunkPos::new(1, 0, 0), ground(5))]);
+    let light = ChunkLight::compute(&low);
+    let border = BorderBlocks::gather(ChunkPos::new(0, 0, 0), |position| chunks.get(&position));
+
+    // Faces of the low chunk towards +x, all of them on its last column
+    let east_faces = |border: &BorderBlocks| {
+        let chunk_data = mesh_chunk_with_borders(&low, &light, border, &BorderLight::default());
+        chunk_data.vertices.iter().filter(|vertex| vertex.normal == [1.0, 0.0, 0.0]).count() / 4
+    };
+    // On its own every border column shows its side, all the way down
+    assert_eq!(east_faces(&BorderBlocks::default()), 16 * 4);
+    let alone = mesh_chunk_with_borders(&low, &light, &BorderBlocks::default(), &BorderLight::default());
+    assert_eq!(alone.vertices.len(), mesh_chunk(&low, &light).vertices.len());
+    // Next to higher ground all of them are hidden
+    assert_eq!(east_faces(&border), 0);
+
+    // Nothing is loaded on the other sides, so their faces are kept
+    assert_eq!(border.get(-1, 2, 4), None);
+    assert_eq!(border.get(16, 5, 4), Some(Block::Rock));
+    assert_eq!(border.get(16, 6, 4), Some(Block::Air));
+}
@@ -0,0 +1,64 @@
+use pixelquest::world::noise_layer::{DomainWarp, Fractal, NoiseKind, NoiseLayer, NoiseSettings};
+
+extern crate pixelquest;
+
+fn sample_row(layer: &NoiseLayer) -> Vec<f64> {
+    (0..64).map(|i| layer.get(i as f64 * 3.7, i as f64 * -1.3)).collect()
+}
+
+#[test]
+fn test_noise_layer_is_deterministic() {
+    for kind in [NoiseKind::Perlin, NoiseKind::OpenSimplex, NoiseKind::Worley] {
+        for fractal in [Fractal::Single, Fractal::Fbm, Fractal::Ridged] {
+            let settings = NoiseSettings {
+                kind,
+                fractal,
+                seed: 7,
+                frequency: 0.05,
+                warp: Some(DomainWarp { frequency: 0.01, amplitude: 10.0 }),
+                ..Default::default()
+            };
+
+            let first = sample_row(&NoiseLayer::new(&settings));
+            let second = sample_row(&NoiseLayer::new(&settings));
+            assert_eq!(first, second, "{:?} {:?} is not deterministic", kind, fractal);
+            assert!(first.iter().all(|value| value.is_finite()));
+        }
+    }
+}
+
+#[test]
+fn test_noise_layer_settings_change_output() {
+    let base = NoiseSettings { seed: 3, frequency: 0.05, ..Default::default() };
+    let reference = sample_row(&NoiseLayer::new(&base));
+
+    let variants = [
+        NoiseSettings { seed: 4, ..base },
+        NoiseSettings { kind: NoiseKind::OpenSimplex, ..base },
+        NoiseSettings { fractal: Fractal::Fbm, ..base },
+        NoiseSettings { fractal: Fractal::Ridged, ..base },
+        NoiseSettings { warp: Some(DomainWarp { frequency: 0.02, amplitude: 25.0 }), ..base },
+    ];
+
+    for settings in variants {
+        assert_ne!(reference, sample_row(&NoiseLayer::new(&settings)), "{:?} matched the base layer", settings);
+    }
+}
+
+#[test]
+fn test_fbm_layer_stays_in_range() {
+    let layer = NoiseLayer::new(&NoiseSettings {
+        fractal: Fractal::Fbm,
+        seed: 142,
+        frequency: 0.01,
+        octaves: 6,
+        ..Default::default()
+    });
+
+    for x in -50..50 {
+        for z in -50..50 {
+            let value = layer.get(x as f64 * 7.0, z as f64 * 7.0);
+            assert!((-1.5..=1.5).contains(&value), "fbm value {} out of range", value);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::chunk::generate_chunk;
+use pixelquest::world::preset::WorldPreset;
+
+extern crate pixelquest;
+
+// Read straight from the blocks, the chunks are never lit or meshed
+fn highest_block(preset: WorldPreset, radius: i32) -> i32 {
+    let config = preset.generator_config(142);
+    let mut highest = i32::MIN;
+
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            let voxels = &generate_chunk(Point3::new(x, 0, z), &config);
+            let heights: Vec<i32> = (0..CHUNK_SIZE)
+                .flat_map(|x| (0..CHUNK_SIZE).filter_map(move |z| voxels.surface_height(x, z)))
+                .collect();
+            assert!(!heights.is_empty(), "{} generated an empty chunk", preset.name());
+            highest = heights.into_iter().fold(highest, i32::max);
+        }
+    }
+
+    highest
+}
+
+#[test]
+fn test_preset_names_round_trip() {
+    for preset in WorldPreset::ALL {
+        assert_eq!(WorldPreset::from_name(preset.name()), Some(preset));
+        assert_eq!(WorldPreset::from_name(&preset.name().to_uppercase()), Some(preset));
+    }
+    assert_eq!(WorldPreset::from_name("nether"), None);
+}
+
+#[test]
+fn test_every_preset_generates_terrain() {
+    for preset in WorldPreset::ALL {
+        let config = preset.generator_config(142);
+        assert!(!config.passes.is_empty());
+        highest_block(preset, 0);
+
+        // Every seed works, the passes offset it for their own noise without overflowing
+        preset.generator_config(u32::MAX);
+    }
+}
+
+#[test]
+fn test_presets_shape_the_terrain() {
+    // Flat worlds only have the ground and trees on it
+    assert!(highest_block(WorldPreset::Flat, 1) < 20);
+    // Island worlds have land floating high up
+    assert!(highest_block(WorldPreset::Islands, 2) > 40);
+}
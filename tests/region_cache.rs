@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use pixelquest::world::region_cache::RegionCache;
+
+extern crate pixelquest;
+
+#[test]
+fn test_threads_racing_for_a_region_compute_it_once() {
+    let cache = RegionCache::new(4);
+    let computed = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let value = cache.get_or_insert_with((1, -2), || {
+                    computed.fetch_add(1, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(50));
+                    7
+                });
+                assert_eq!(*value, 7);
+            });
+        }
+    });
+
+    assert_eq!(computed.load(Ordering::SeqCst), 1);
+    assert_eq!(cache.len(), 1);
+}
@@ -0,0 +1,42 @@
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::render_distance::RenderDistance;
+
+extern crate pixelquest;
+
+#[test]
+fn test_chunks_load_in_a_circle() {
+    let render_distance = RenderDistance::new(5);
+    let camera_chunk = ChunkPos::new(3, 0, -2);
+    let chunks: Vec<ChunkPos> = render_distance.chunks_to_load(camera_chunk).collect();
+
+    assert!(chunks.contains(&ChunkPos::new(8, 0, -2)));
+    assert!(chunks.contains(&ChunkPos::new(6, 0, 2)));
+    // The corners of the square are left out
+    assert!(!chunks.contains(&ChunkPos::new(7, 0, 2)));
+    assert!(chunks.iter().all(|&chunk| render_distance.should_load(chunk, camera_chunk)));
+    // Roughly the area of the circle
+    assert!(chunks.len() > 70 && chunks.len() < 100, "{} chunks", chunks.len());
+}
+
+#[test]
+fn test_unloading_lags_behind_loading() {
+    let render_distance = RenderDistance::new(5);
+    let origin = ChunkPos::new(0, 0, 0);
+
+    // Just past the load radius stays loaded until past the unload radius
+    assert!(!render_distance.should_load(ChunkPos::new(6, 0, 0), origin));
+    assert!(!render_distance.should_unload(ChunkPos::new(6, 0, 0), origin));
+    assert!(!render_distance.should_unload(ChunkPos::new(7, 0, 0), origin));
+    assert!(render_distance.should_unload(ChunkPos::new(8, 0, 0), origin));
+
+    // Nothing that gets loaded is unloaded right away
+    for chunk in render_distance.chunks_to_load(origin) {
+        assert!(!render_distance.should_unload(chunk, origin));
+    }
+}
+
+#[test]
+fn test_render_distance_is_clamped() {
+    assert_eq!(RenderDistance::new(0).load_radius, RenderDistance::MIN_RADIUS);
+    assert_eq!(RenderDistance::new(1000).load_radius, RenderDistance::MAX_RADIUS);
+}
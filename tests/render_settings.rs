@@ -0,0 +1,26 @@
+use pixelquest::renderer::settings::{RenderSettings, TextureFiltering};
+
+extern crate pixelquest;
+
+#[test]
+fn test_texture_filtering_steps_through_every_combination() {
+    let mut settings = RenderSettings::default();
+    assert_eq!(settings.texture_filtering(), Some(TextureFiltering::Anisotropic));
+
+    let mut filtering = TextureFiltering::Anisotropic;
+    for _ in TextureFiltering::ALL {
+        filtering = filtering.next();
+        settings.set_texture_filtering(filtering);
+        assert_eq!(settings.texture_filtering(), Some(filtering));
+    }
+    assert_eq!(filtering, TextureFiltering::Anisotropic);
+
+    // Other settings are left alone
+    settings.set_texture_filtering(TextureFiltering::Nearest);
+    assert_eq!(settings.mipmap_filter, None);
+    assert_eq!(settings.shadow_map_size, RenderSettings::default().shadow_map_size);
+
+    // Filters set by hand don't match any of them
+    settings.anisotropy = 16;
+    assert_eq!(settings.texture_filtering(), None);
+}
@@ -0,0 +1,102 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::block::Block;
+use pixelquest::world::chunk::generate_chunk;
+use pixelquest::world::generator::GeneratorPass;
+use pixelquest::world::heightfield::HeightField;
+use pixelquest::world::preset::WorldPreset;
+use pixelquest::world::road::{plan_road, points_of_interest, Bounds, Road, RoadNetwork, RoadSettings};
+
+extern crate pixelquest;
+
+fn road_network(passes: &[GeneratorPass]) -> &RoadNetwork {
+    passes
+        .iter()
+        .find_map(|pass| match pass {
+            GeneratorPass::Roads(network) => Some(network.as_ref()),
+            _ => None,
+        })
+        .expect("preset has no roads")
+}
+
+#[test]
+fn test_roads_go_around_cliffs() {
+    // A cliff across the whole area with a single gap in it
+    let surface = HeightField::new(|x, z| if (40..48).contains(&x) && !(80..96).contains(&z) { 50 } else { 0 });
+    let bounds = Bounds { min_x: 0, min_z: 0, max_x: 128, max_z: 128 };
+    let road = plan_road(&surface, (10, 10), (100, 10), bounds, &RoadSettings::default()).expect("no road found");
+
+    assert_eq!((road.points[0].x, road.points[0].z), (10, 10));
+    let last = road.points.last().unwrap();
+    assert_eq!((last.x, last.z), (100, 10));
+
+    for point in &road.points {
+        assert!(point.x >= 0 && point.x < 128 && point.z >= 0 && point.z < 128);
+        if (40..48).contains(&point.x) {
+            assert!((80..96).contains(&point.z), "road climbs the cliff at {:?}", point);
+        }
+    }
+
+    // Without the gap there is no way across
+    let walled = HeightField::new(|x, _| if (40..48).contains(&x) { 50 } else { 0 });
+    assert!(plan_road(&walled, (10, 10), (100, 10), bounds, &RoadSettings::default()).is_none());
+}
+
+#[test]
+fn test_road_networks_connect_structures() {
+    let config = WorldPreset::Default.generator_config(142);
+    let network = road_network(&config.passes);
+    let surface = config.surface_height_field();
+
+    let roads = network.region_roads(&surface, (0, 0));
+    assert_eq!(network.cached_regions(), 1);
+    assert_eq!(network.region_roads(&surface, (0, 0)), roads);
+
+    let region_points = |region| points_of_interest(network.structures(), network.settings().region_cells, region, &surface);
+    let points = region_points((0, 0));
+    let neighbour_points = [region_points((1, 0)), region_points((0, 1))].concat();
+    assert!(points.len() > 1);
+
+    // Roads inside the region join up its points, the ones leaving it lead to a point of a neighbour
+    let bounds = network.region_bounds((0, 0));
+    let (inside, leaving): (Vec<&Road>, Vec<&Road>) = roads.iter().partition(|road| {
+        road.points.iter().all(|point| point.x > bounds.min_x && point.x < bounds.max_x - 1 && point.z > bounds.min_z && point.z < bounds.max_z - 1)
+    });
+    for road in &inside {
+        let (first, last) = (road.points.first().unwrap(), road.points.last().unwrap());
+        assert!(points.contains(&(first.x, first.z)));
+        assert!(points.contains(&(last.x, last.z)));
+    }
+    for road in &leaving {
+        let (first, last) = (road.points.first().unwrap(), road.points.last().unwrap());
+        assert!(points.contains(&(first.x, first.z)));
+        assert!(neighbour_points.contains(&(last.x, last.z)));
+
+        // Chunks on the neighbour's side find the road too
+        let chunk_position = Point3::new(last.x.div_euclid(CHUNK_SIZE), 0, last.z.div_euclid(CHUNK_SIZE));
+        assert!(network.roads_intersecting(chunk_position, &surface).contains(road));
+    }
+
+    // A spanning tree at most, roads the ground doesn't allow are left out
+    assert!(!inside.is_empty() && inside.len() < points.len());
+    assert!(!leaving.is_empty() && leaving.len() <= 2);
+}
+
+#[test]
+fn test_roads_are_laid_into_chunks() {
+    let config = WorldPreset::Flat.generator_config(142);
+    let network = road_network(&config.passes);
+    let surface = config.surface_height_field();
+    let roads = network.region_roads(&surface, (0, 0));
+    let road = roads.iter().max_by_key(|road| road.points.len()).expect("no roads planned");
+
+    // The middle of the longest road is well away from the structures at its ends
+    let middle = road.points[road.points.len() / 2];
+    let chunk_position = Point3::new(middle.x.div_euclid(CHUNK_SIZE), 0, middle.z.div_euclid(CHUNK_SIZE));
+    let voxels = generate_chunk(chunk_position, &config);
+
+    let (x, z) = (middle.x.rem_euclid(CHUNK_SIZE), middle.z.rem_euclid(CHUNK_SIZE));
+    assert_eq!(middle.height, 0);
+    assert_eq!(voxels.get(x, 0, z), Block::Path);
+    assert!((1..4).all(|y| voxels.get(x, y, z).is_air()));
+}
@@ -0,0 +1,60 @@
+use cgmath::{perspective, Deg, Matrix4, Point3, Vector3};
+use pixelquest::camera::camera::Camera;
+use pixelquest::renderer::shadows::{cascade_splits, fit_light_space, frustum_slice_corners, perspective_depth_range};
+
+extern crate pixelquest;
+
+#[test]
+fn test_cascades_grow_with_distance_and_cover_the_range() {
+    let projection = perspective(Deg(45.0), 1.5, 0.1, 900.0);
+    let (near, far) = perspective_depth_range(projection);
+    assert!((near - 0.1).abs() < 1e-4 && (far - 900.0).abs() < 0.5);
+
+    let splits = cascade_splits(0.1, 128.0, 3);
+    assert_eq!(splits.len(), 3);
+    assert!((splits[2] - 128.0).abs() < 1e-3);
+    // Each cascade is longer than the one before it
+    let lengths = [splits[0] - 0.1, splits[1] - splits[0], splits[2] - splits[1]];
+    assert!(lengths[0] > 0.0 && lengths[0] < lengths[1] && lengths[1] < lengths[2]);
+}
+
+#[test]
+fn test_light_space_covers_the_frustum_slice() {
+    let camera = Camera::new(Point3::new(1_000_000.5, 80.0, -2_000_000.25), Vector3::new(0.0, 1.0, 0.0), -60.0, -20.0);
+    let projection = perspective(Deg(45.0), 1.5, 0.1, 900.0);
+    let light = Vector3::new(0.4, 0.8, 0.3);
+
+    for (near, far) in [(0.1, 12.0), (12.0, 48.0), (48.0, 128.0)] {
+        let corners = frustum_slice_corners(projection, camera.get_view_matrix(), near, far);
+        let light_space = fit_light_space(&corners, light, camera.position, 1024, 256.0);
+        for corner in corners {
+            let clip = light_space * corner.extend(1.0);
+            assert!(clip.x.abs() <= 1.0 && clip.y.abs() <= 1.0 && clip.z.abs() <= 1.0, "{:?} is outside", clip);
+        }
+    }
+}
+
+#[test]
+fn test_shadow_texels_stay_put_in_the_world_as_the_camera_moves() {
+    let light = Vector3::new(-0.3, 0.9, 0.2);
+    let projection = perspective(Deg(45.0), 1.5, 0.1, 900.0);
+    let block = Point3::new(5000.0, 64.0, -3000.0);
+
+    // Where the block lands on the shadow map, in texels
+    let texel_position = |camera: &Camera| {
+        let corners = frustum_slice_corners(projection, camera.get_view_matrix(), 0.1, 32.0);
+        let light_space: Matrix4<f32> = fit_light_space(&corners, light, camera.position, 1024, 256.0);
+        let clip = light_space * camera.relative_position(block).extend(1.0);
+        (clip.x * 512.0, clip.y * 512.0)
+    };
+
+    let mut camera = Camera::new(Point3::new(4990.0, 70.0, -2990.0), Vector3::new(0.0, 1.0, 0.0), 30.0, -10.0);
+    let (x, y) = texel_position(&camera);
+    camera.translate(Vector3::new(0.37, 0.11, -0.23));
+    let (moved_x, moved_y) = texel_position(&camera);
+
+    // The map may have moved by whole texels, but never by part of one
+    let fraction = |value: f32| value - value.round();
+    assert!((fraction(x) - fraction(moved_x)).abs() < 0.01);
+    assert!((fraction(y) - fraction(moved_y)).abs() < 0.01);
+}
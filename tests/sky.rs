@@ -0,0 +1,29 @@
+use cgmath::Vector3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::renderer::sky::{fog_distances, SkyColours};
+use pixelquest::world::render_distance::RenderDistance;
+
+extern crate pixelquest;
+
+#[test]
+fn test_fog_is_thick_before_the_edge_of_the_loaded_chunks() {
+    let mut last_end = 0.0;
+    for radius in 2..12 {
+        let (start, end) = fog_distances(RenderDistance::new(radius));
+        assert!(start < end);
+        assert!(end <= (radius * CHUNK_SIZE) as f32);
+        // Loading more chunks pushes the fog back
+        assert!(end > last_end);
+        last_end = end;
+    }
+}
+
+#[test]
+fn test_sky_is_a_gradient_from_the_horizon_up() {
+    let sky = SkyColours::default();
+
+    assert_eq!(sky.colour(Vector3::new(0.0, 1.0, 0.0)), sky.zenith);
+    assert_eq!(sky.colour(Vector3::new(1.0, 0.0, 0.0)), sky.horizon);
+    // Fogged terrain below the horizon fades into the same colour
+    assert_eq!(sky.colour(Vector3::new(0.0, -0.5, 1.0)), sky.horizon);
+}
@@ -0,0 +1,122 @@
+use cgmath::Point3;
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::chunk::generate_chunk;
+use pixelquest::world::generator::{GeneratorConfig, GeneratorPass};
+use pixelquest::world::preset::WorldPreset;
+use pixelquest::world::structure::{structures_in_cell, structures_intersecting, StructurePiece, StructureSettings};
+
+extern crate pixelquest;
+
+fn structure_settings(config: &GeneratorConfig) -> StructureSettings {
+    config
+        .passes
+        .iter()
+        .find_map(|pass| match pass {
+            GeneratorPass::Structures(settings) => Some(*settings),
+            _ => None,
+        })
+        .expect("preset has no structures")
+}
+
+fn pieces_around(config: &GeneratorConfig, cells: i32) -> Vec<(i32, i32, StructurePiece)> {
+    let settings = structure_settings(config);
+    let surface = config.surface_height_field();
+    let mut pieces = Vec::new();
+
+    for cell_x in -cells..cells {
+        for cell_z in -cells..cells {
+            for piece in structures_in_cell(&settings, cell_x, cell_z, &surface) {
+                pieces.push((cell_x, cell_z, piece));
+            }
+        }
+    }
+    pieces
+}
+
+#[test]
+fn test_structures_are_deterministic_and_stay_in_their_cell() {
+    let config = WorldPreset::Default.generator_config(142);
+    let settings = structure_settings(&config);
+    let first = pieces_around(&config, 3);
+    let second = pieces_around(&config, 3);
+
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+
+    for (cell_x, cell_z, piece) in first {
+        assert!(piece.min.x >= cell_x * settings.cell_size && piece.max().x <= (cell_x + 1) * settings.cell_size);
+        assert!(piece.min.z >= cell_z * settings.cell_size && piece.max().z <= (cell_z + 1) * settings.cell_size);
+    }
+}
+
+#[test]
+fn test_cells_too_small_for_a_structure_are_left_empty() {
+    let config = WorldPreset::Default.generator_config(142);
+    let surface = config.surface_height_field();
+
+    for cell_size in 1..64 {
+        let settings = StructureSettings { cell_size, chance: 1.0, ..structure_settings(&config) };
+        for cell_x in -4..4 {
+            for cell_z in -4..4 {
+                for piece in structures_in_cell(&settings, cell_x, cell_z, &surface) {
+                    assert!(piece.min.x >= cell_x * cell_size && piece.max().x <= (cell_x + 1) * cell_size);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_chunks_find_the_pieces_reaching_into_them() {
+    let config = WorldPreset::Default.generator_config(142);
+    let settings = structure_settings(&config);
+    let surface = config.surface_height_field();
+    let pieces = pieces_around(&config, 2);
+
+    for chunk_x in -12..12 {
+        for chunk_z in -12..12 {
+            let chunk_position = Point3::new(chunk_x, 0, chunk_z);
+            let expected: Vec<StructurePiece> = pieces
+                .iter()
+                .map(|(_, _, piece)| *piece)
+                .filter(|piece| piece.intersects_chunk(chunk_position))
+                .collect();
+
+            assert_eq!(structures_intersecting(&settings, chunk_position, &surface), expected);
+        }
+    }
+}
+
+#[test]
+fn test_pieces_spanning_chunks_are_built_in_every_chunk() {
+    let config = WorldPreset::Flat.generator_config(142);
+    let pieces = pieces_around(&config, 2);
+    let piece = pieces
+        .iter()
+        .map(|(_, _, piece)| *piece)
+        .find(|piece| {
+            piece.min.x.div_euclid(CHUNK_SIZE) != (piece.max().x - 1).div_euclid(CHUNK_SIZE)
+                || piece.min.z.div_euclid(CHUNK_SIZE) != (piece.max().z - 1).div_euclid(CHUNK_SIZE)
+        })
+        .expect("no piece spans more than one chunk");
+
+    // On flat ground every piece sits right on top of it
+    assert_eq!(piece.min.y, 1);
+
+    for chunk_x in piece.min.x.div_euclid(CHUNK_SIZE)..=(piece.max().x - 1).div_euclid(CHUNK_SIZE) {
+        for chunk_z in piece.min.z.div_euclid(CHUNK_SIZE)..=(piece.max().z - 1).div_euclid(CHUNK_SIZE) {
+            let voxels = generate_chunk(Point3::new(chunk_x, 0, chunk_z), &config);
+
+            for x in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    for y in piece.min.y..piece.max().y {
+                        let position = Point3::new(chunk_x * CHUNK_SIZE + x, y, chunk_z * CHUNK_SIZE + z);
+                        if let Some(block) = piece.block_at(position) {
+                            assert_eq!(voxels.get(x, y, z), block, "{:?} at {:?}", piece.kind, position);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::PathBuf;
+
+use image::{ImageFormat, Rgba, RgbImage, RgbaImage};
+use pixelquest::graphics::texture_pack::{
+    missing_texture, TextureManifest, TexturePack, TexturePackError, DEFAULT_TEXTURE_PACK, MANIFEST_FILE, MISSING_TEXTURE,
+};
+use pixelquest::world::block::Block;
+
+extern crate pixelquest;
+
+const BLOCKS: &str = "
+    tile_size = 16
+    dark-grass = grass.png side.png dirt.png # top, side and bottom
+    light-grass = grass.png side.png dirt.png
+    sand = sand.png
+    rock = sand.png
+    dirt = dirt.png
+    wood = dirt.png
+    water = sand.png
+    path = sand.png
+    torch = sand.png
+    lava = sand.png
+";
+
+// A fresh pack directory with the given images in it
+fn pack_directory(name: &str, images: &[(&str, u32, u32)]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("pixelquest-texture-pack-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join(MANIFEST_FILE), BLOCKS).unwrap();
+    for &(image, width, height) in images {
+        RgbaImage::from_pixel(width, height, Rgba([90, 160, 40, 255])).save(directory.join(image)).unwrap();
+    }
+    directory
+}
+
+#[test]
+fn test_manifest_maps_blocks_to_images() {
+    let manifest = TextureManifest::parse(BLOCKS).unwrap();
+    assert_eq!(manifest.tile_size, 16);
+    assert_eq!(manifest.images, ["grass.png", "side.png", "dirt.png", "sand.png"]);
+    assert_eq!(manifest.block_images(Block::DarkGrass), Some(["grass.png", "side.png", "dirt.png"]));
+    assert_eq!(manifest.block_images(Block::Water), Some(["sand.png"; 3]));
+
+    let uvs = &manifest.block_uvs()[Block::DarkGrass as usize];
+    assert_eq!(uvs.top, manifest.layout().tile_uvs(0));
+    assert_eq!(uvs.left, manifest.layout().tile_uvs(1));
+
+    assert!(manifest.missing_blocks.is_empty());
+    let unknown_block = format!("{}\nobsidian = obsidian.png", BLOCKS);
+    assert!(matches!(TextureManifest::parse(&unknown_block), Err(TexturePackError::Manifest { line: 14, .. })));
+    let two_images = BLOCKS.replace("sand = sand.png", "sand = sand.png dirt.png");
+    assert!(matches!(TextureManifest::parse(&two_images), Err(TexturePackError::Manifest { line: 5, .. })));
+}
+
+#[test]
+fn test_packs_only_load_with_matching_square_pngs() {
+    let images = [("grass.png", 8, 8), ("side.png", 8, 8), ("dirt.png", 8, 8), ("sand.png", 8, 8)];
+    let directory = pack_directory("valid", &images);
+    let pack = TexturePack::load(&directory).unwrap();
+    assert_eq!(pack.tiles.len(), 4);
+    // Scaled up to the manifest's tile size, all on one page
+    let pages = pack.atlas_pages();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].width(), pack.manifest.layout().page_size);
+
+    let directory = pack_directory("mismatch", &[("grass.png", 8, 8), ("side.png", 16, 16), ("dirt.png", 8, 8), ("sand.png", 8, 8)]);
+    assert!(matches!(TexturePack::load(&directory), Err(TexturePackError::SizeMismatch { expected: 8, found: (16, 16), .. })));
+
+    let directory = pack_directory("not-square", &[("grass.png", 8, 4), ("side.png", 8, 4), ("dirt.png", 8, 4), ("sand.png", 8, 4)]);
+    assert!(matches!(TexturePack::load(&directory), Err(TexturePackError::NotSquare { .. })));
+
+    // Files called .png that aren't one
+    let directory = pack_directory("format", &images);
+    RgbImage::new(8, 8).save_with_format(directory.join("sand.png"), ImageFormat::Jpeg).unwrap();
+    assert!(matches!(TexturePack::load(&directory), Err(TexturePackError::UnsupportedFormat(..))));
+    fs::write(directory.join("sand.png"), "not an image").unwrap();
+    assert!(matches!(TexturePack::load(&directory), Err(TexturePackError::Image(..))));
+
+    let directory = pack_directory("missing", &images[..3]);
+    assert!(matches!(TexturePack::load(&directory), Err(TexturePackError::Io(..))));
+
+    for name in ["valid", "mismatch", "not-square", "format", "missing"] {
+        let _ = fs::remove_dir_all(pack_directory(name, &[]));
+    }
+}
+
+#[test]
+fn test_blocks_without_a_texture_get_the_missing_texture() {
+    let missing_path = BLOCKS.replace("path = sand.png", "").replace("torch = sand.png", "");
+    let manifest = TextureManifest::parse(&missing_path).unwrap();
+    assert_eq!(manifest.missing_blocks, [Block::Path, Block::Torch]);
+    assert_eq!(manifest.block_images(Block::Path), Some([MISSING_TEXTURE; 3]));
+    assert_eq!(manifest.images.iter().filter(|&image| image == MISSING_TEXTURE).count(), 1);
+
+    // The pack builds the tile itself, at the size of its other images
+    let directory = pack_directory("missing-block", &[("grass.png", 8, 8), ("side.png", 8, 8), ("dirt.png", 8, 8), ("sand.png", 8, 8)]);
+    fs::write(directory.join(MANIFEST_FILE), &missing_path).unwrap();
+    let pack = TexturePack::load(&directory).unwrap();
+    assert_eq!(pack.tiles.last(), Some(&missing_texture(8)));
+    let _ = fs::remove_dir_all(directory);
+}
+
+#[test]
+fn test_default_pack_loads() {
+    let pack = TexturePack::load(DEFAULT_TEXTURE_PACK).unwrap();
+    assert!(!pack.atlas_pages().is_empty());
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use pixelquest::constants::world::CHUNK_SIZE;
+use pixelquest::world::block::Block;
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::visibility::{visible_chunks, ChunkVisibility};
+use pixelquest::world::voxels::{ChunkVoxels, CHUNK_HEIGHT};
+
+extern crate pixelquest;
+
+// Faces numbered like `FACE_DIRECTIONS`
+const FRONT: usize = 0;
+const BACK: usize = 1;
+const TOP: usize = 2;
+const RIGHT: usize = 4;
+const LEFT: usize = 5;
+
+#[test]
+fn test_faces_connect_through_air() {
+    assert_eq!(ChunkVisibility::compute(&ChunkVoxels::new()), ChunkVisibility::open());
+
+    let mut voxels = ChunkVoxels::new();
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_HEIGHT {
+                voxels.set(x, y, z, Block::Rock);
+            }
+        }
+    }
+    assert_eq!(ChunkVisibility::compute(&voxels), ChunkVisibility::default());
+
+    // A tunnel straight through from the left face to the right one, plus a sealed cave
+    for x in 0..CHUNK_SIZE {
+        voxels.set(x, 10, 8, Block::Air);
+    }
+    voxels.set(5, 40, 5, Block::Air);
+    let visibility = ChunkVisibility::compute(&voxels);
+    assert!(visibility.connects(LEFT, RIGHT) && visibility.connects(RIGHT, LEFT));
+    assert!(!visibility.connects(FRONT, BACK));
+    assert!(!visibility.connects(LEFT, TOP));
+}
+
+#[test]
+fn test_chunks_behind_sealed_chunks_are_hidden() {
+    // A field of open chunks with a sealed one right next to the camera chunk
+    let mut chunks: HashMap<ChunkPos, ChunkVisibility> = HashMap::new();
+    for x in -4..=4 {
+        for z in -4..=4 {
+            chunks.insert(ChunkPos::new(x, 0, z), ChunkVisibility::open());
+        }
+    }
+    chunks.insert(ChunkPos::new(1, 0, 0), ChunkVisibility::default());
+
+    let visible = visible_chunks(ChunkPos::new(0, 0, 0), |position| chunks.get(&position).copied()).unwrap();
+    // The sealed chunk itself can be seen, just not through
+    assert!(visible.contains(&ChunkPos::new(1, 0, 0)));
+    assert!(!visible.contains(&ChunkPos::new(2, 0, 0)));
+    assert!(!visible.contains(&ChunkPos::new(4, 0, 0)));
+    // Chunks beside it are seen past its edges
+    assert!(visible.contains(&ChunkPos::new(2, 0, 1)));
+    assert!(visible.contains(&ChunkPos::new(-4, 0, -4)));
+    assert_eq!(visible.len(), chunks.len() - 3);
+
+    // Nothing can be ruled out from a camera chunk that isn't loaded
+    assert!(visible_chunks(ChunkPos::new(0, 1, 0), |position| chunks.get(&position).copied()).is_none());
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use pixelquest::world::border::BorderBlocks;
+use pixelquest::world::coords::ChunkPos;
+use pixelquest::world::chunk::GeneratedChunk;
+use pixelquest::world::generator::GeneratorPass;
+use pixelquest::world::light::BorderLight;
+use pixelquest::world::mesher::mesh_chunk;
+use pixelquest::world::preset::WorldPreset;
+use pixelquest::world::structure::StructureSettings;
+use pixelquest::world::workers::{ChunkWorkers, FinishedJob};
+
+extern crate pixelquest;
+
+fn wait_for(workers: &ChunkWorkers, count: usize) -> Vec<FinishedJob> {
+    let mut finished = Vec::new();
+    let started = Instant::now();
+    while finished.len() < count {
+        assert!(started.elapsed() < Duration::from_secs(60), "workers never finished");
+        finished.extend(workers.finished());
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    finished
+}
+
+#[test]
+fn test_workers_generate_chunks_off_thread() {
+    let config = WorldPreset::Default.generator_config(142);
+    let mut workers = ChunkWorkers::new(config.clone(), 3);
+    assert_eq!(workers.thread_count(), 3);
+
+    // All in one road region, so its roads are only planned once
+    let positions: Vec<ChunkPos> = (0..2).flat_map(|x| (0..2).map(move |z| ChunkPos::new(x, 0, z))).collect();
+    for &position in &positions {
+        workers.submit(position).unwrap();
+    }
+
+    let finished = wait_for(&workers, positions.len());
+
+    let finished_positions: HashSet<ChunkPos> = finished.iter().map(|chunk| chunk.position).collect();
+    assert_eq!(finished_positions, positions.iter().copied().collect());
+
+    // Workers build the same mesh as generating on this thread, down to which block every face is
+    for job in &finished {
+        let chunk = job.chunk.as_ref().expect("job was never cancelled");
+        let expected = GeneratedChunk::generate(chunk.position, &config);
+        assert_eq!(chunk.chunk_data.indices, expected.chunk_data.indices);
+        assert!(chunk.chunk_data.vertices == expected.chunk_data.vertices, "{:?} came out differently", chunk.position);
+    }
+}
+
+#[test]
+fn test_cancelled_jobs_are_abandoned() {
+    let config = WorldPreset::Flat.generator_config(142);
+    // No workers until the jobs are cancelled, so none of them can be started before that
+    let mut workers = ChunkWorkers::new(config, 0);
+
+    let jobs: Vec<_> = (0..20).map(|x| workers.submit(ChunkPos::new(x, 0, 0)).unwrap()).collect();
+    for job in &jobs[1..] {
+        job.cancel();
+    }
+    workers.add_threads(2);
+
+    // Every job comes back exactly once, only the one left alone with its chunk
+    let finished = wait_for(&workers, jobs.len());
+    assert_eq!(finished.len(), jobs.len());
+    for job in &finished {
+        assert_eq!(job.chunk.is_some(), job.id == jobs[0].id);
+    }
+}
+
+#[test]
+fn test_panicking_jobs_come_back_abandoned() {
+    // Structures in cells of no size at all divide by zero
+    let mut config = WorldPreset::Flat.generator_config(142);
+    config.passes.push(GeneratorPass::Structures(StructureSettings { cell_size: 0, ..Default::default() }));
+    let mut workers = ChunkWorkers::new(config, 1);
+
+    // The one worker survives every panic and gets to all the jobs
+    let jobs: Vec<_> = (0..3).map(|x| workers.submit(ChunkPos::new(x, 0, 0)).unwrap()).collect();
+    let finished = wait_for(&workers, jobs.len());
+    assert!(finished.iter().all(|job| job.chunk.is_none()));
+}
+
+#[test]
+fn test_workers_remesh_copies_of_loaded_chunks() {
+    let config = WorldPreset::Flat.generator_config(142);
+    let mut workers = ChunkWorkers::new(config.clone(), 0);
+    let loaded = GeneratedChunk::generate(ChunkPos::new(0, 0, 0), &config);
+
+    let remesh = |workers: &mut ChunkWorkers| {
+        workers.submit_remesh(loaded.position, loaded.voxels.clone(), loaded.light.clone(), BorderBlocks::default(), BorderLight::default()).unwrap()
+    };
+    let kept = remesh(&mut workers);
+    // Remeshing can stop once cancelled, the chunk's blocks are still loaded
+    remesh(&mut workers).cancel();
+    workers.add_threads(1);
+
+    let finished = wait_for(&workers, 2);
+    assert!(finished.iter().all(|job| job.remesh));
+    for job in &finished {
+        assert_eq!(job.chunk.is_some(), job.id == kept.id);
+    }
+    let remeshed = finished.iter().find_map(|job| job.chunk.as_ref()).unwrap();
+    // Without any chunks next to it the border faces are lit like the chunk on its own
+    assert_eq!(remeshed.chunk_data.indices, mesh_chunk(&loaded.voxels, &loaded.light).indices);
+}
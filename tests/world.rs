@@ -0,0 +1,10 @@
+use pixelquest::world::coords::{BlockPos, ChunkPos, LocalPos};
+
+extern crate pixelquest;
+
+#[test]
+fn test_block_positions_split_into_chunk_and_local() {
+    assert_eq!(BlockPos::new(17, 40, 3).split(), (ChunkPos::new(1, 0, 0), LocalPos::new(1, 40, 3)));
+    assert_eq!(BlockPos::new(-1, 5, -16).split(), (ChunkPos::new(-1, 0, -1), LocalPos::new(15, 5, 0)));
+    assert_eq!(BlockPos::new(-17, 0, 0).split(), (ChunkPos::new(-2, 0, 0), LocalPos::new(15, 0, 0)));
+}